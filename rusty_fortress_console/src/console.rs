@@ -1,13 +1,37 @@
+use std::cell::Cell;
+use std::env;
 use std::io;
+use std::io::IsTerminal;
 use std::io::Write;
 use rusty_fortress_lib::app::app;
+use rusty_fortress_lib::app::style::Style;
+use rusty_fortress_lib::app::style::StyledLine;
 
-pub struct Console {}
+// ANSI code colouring each style, if any
+fn ansi(style: &Style) -> Option<&'static str> {
+    match style {
+        Style::Plain => Option::None,
+        Style::Title => Option::Some("1"),
+        Style::Emphasis => Option::Some("1;33"),
+        Style::Object => Option::Some("36"),
+        Style::Error => Option::Some("31"),
+        Style::System => Option::Some("2")
+    }
+}
+
+pub struct Console {
+    ended: Cell<bool>,
+    // Whether the output is coloured, only on a terminal and unless NO_COLOR is set
+    colours: bool
+}
 
 impl Console {
 
     pub fn new() -> Console {
-        Console {}
+        Console {
+            ended: Cell::new(false),
+            colours: io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+        }
     }
 
     fn prompt(&self) {
@@ -23,6 +47,11 @@ impl app::InOut for Console {
         let mut line = String::new();
         self.prompt();    
         match io::stdin().read_line(&mut line) {
+            // Nothing more to read, as when a script is piped in
+            Ok(0) => {
+                println!();
+                self.ended.set(true);
+            },
             Ok(_) => {},
             Err(_) => line = String::new()
         }
@@ -33,4 +62,20 @@ impl app::InOut for Console {
         println!("{}", line);
     }
 
+    fn write_styled(&self, line: StyledLine) {
+        if !self.colours {
+            return self.write_line(line.text());
+        }
+        let text: String = line.spans.iter().map(|span| match ansi(&span.style) {
+            // Every line of a span is coloured on its own, so a reset never runs over a line end
+            Option::Some(code) => span.text.split('\n').map(|t| format!("\x1b[{}m{}\x1b[0m", code, t)).collect::<Vec<String>>().join("\n"),
+            Option::None => span.text.clone()
+        }).collect();
+        println!("{}", text);
+    }
+
+    fn has_ended(&self) -> bool {
+        self.ended.get()
+    }
+
 }
@@ -1,8 +1,186 @@
+use std::env;
+use std::fs;
+use std::process;
 use rusty_fortress_lib::app::app;
+use rusty_fortress_lib::app::locale::Locale;
+use rusty_fortress_lib::app::script;
+use rusty_fortress_lib::app::solver;
+use rusty_fortress_lib::universe::checker;
+use rusty_fortress_lib::universe::generator;
+use rusty_fortress_lib::universe::rng::Rng;
 
 mod console;
 
+// Most states the solver explores before giving up
+const SOLVE_LIMIT: usize = 20000;
+
+fn usage(error: &str) -> ! {
+    eprintln!("{}", error);
+    eprintln!("Usage: rusty_fortress_console [--seed <number>] [--locale <id>] [--world <file> | --generate [--export <file>]]");
+    eprintln!("       rusty_fortress_console [--seed <number>] [--world <file> | --generate] --solve <goal>");
+    eprintln!("       rusty_fortress_console [--seed <number>] [--world <file> | --generate] --record <commands>");
+    eprintln!("       rusty_fortress_console [--seed <number>] [--world <file>] --test <transcript>...");
+    eprintln!("       rusty_fortress_console --check <file>");
+    process::exit(2);
+}
+
+// Reports the rooms, items and locked doors the player can never get to
+fn check(file: &str) -> ! {
+    let text = fs::read_to_string(file).unwrap_or_else(|e| usage(&format!("Cannot read {}: {}", file, e)));
+    let problems = match text.parse::<toml::Value>() {
+        Ok(value) => checker::check(&value),
+        Err(e) => Err(format!("Invalid world file: {}", e))
+    };
+    match problems {
+        Ok(problems) if problems.is_empty() => {
+            println!("{}: the world can be solved", file);
+            process::exit(0);
+        },
+        Ok(problems) => {
+            problems.iter().for_each(|problem| println!("{}: {}", file, problem));
+            process::exit(1);
+        },
+        Err(e) => {
+            println!("{}: {}", file, e);
+            process::exit(1);
+        }
+    }
+}
+
+// Prints the shortest walkthrough reaching the goal, one command per line
+fn solve(options: &app::Options, goal: &str) -> ! {
+    let goal = solver::Goal::from_data(goal).unwrap_or_else(|e| usage(&e));
+    let result = app::load(options)
+        .and_then(|world| solver::solve(&world, &goal, &app::locale(options)?, SOLVE_LIMIT));
+    match result {
+        Ok(Option::Some(commands)) => {
+            commands.iter().for_each(|command| println!("{}", command));
+            eprintln!("Goal '{}' reached in {} commands", goal.to_data(), commands.len());
+            process::exit(0);
+        },
+        Ok(Option::None) => eprintln!("Goal '{}' can't be reached", goal.to_data()),
+        Err(e) => eprintln!("{}", e)
+    }
+    process::exit(1);
+}
+
+// Plays the commands in the file and prints the transcript of the game
+fn record(options: &app::Options, file: &str) -> ! {
+    let commands = script::Script::from_file(file).unwrap_or_else(|e| usage(&e));
+    script::record(commands, options.clone()).iter().for_each(|line| println!("{}", line));
+    process::exit(0);
+}
+
+// Compares each game with its golden transcript and reports the differences
+fn test(options: &app::Options, files: &[String]) -> ! {
+    let mut failed = 0;
+    for file in files.iter() {
+        match script::test_file(file, options.clone()) {
+            Ok(diffs) if diffs.is_empty() => println!("{}: ok", file),
+            Ok(diffs) => {
+                failed += 1;
+                println!("{}: {} differences", file, diffs.len());
+                diffs.iter().for_each(|diff| println!("{}", diff));
+            },
+            Err(e) => {
+                failed += 1;
+                println!("{}: {}", file, e);
+            }
+        }
+    }
+    process::exit(if failed == 0 { 0 } else { 1 });
+}
+
 fn main() {
+    let mut seed: Option<u64> = Option::None;
+    let mut world: Option<String> = Option::None;
+    let mut locale: Option<String> = Option::None;
+    let mut generate = false;
+    let mut export: Option<String> = Option::None;
+    let mut goal: Option<String> = Option::None;
+    let mut commands: Option<String> = Option::None;
+    let mut transcripts: Vec<String> = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Option::Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => match args.next().and_then(|s| s.parse::<u64>().ok()) {
+                Option::Some(n) => seed = Option::Some(n),
+                Option::None => usage("--seed needs a number")
+            },
+            "--world" => match args.next() {
+                Option::Some(file) => world = Option::Some(file),
+                Option::None => usage("--world needs a file")
+            },
+            "--locale" => match args.next() {
+                Option::Some(id) => locale = Option::Some(id),
+                Option::None => usage(&format!("--locale needs one of: {}", Locale::ids().join(", ")))
+            },
+            "--generate" => generate = true,
+            "--solve" => match args.next() {
+                Option::Some(text) => goal = Option::Some(text),
+                Option::None => usage("--solve needs a goal, such as victory, in:<room> or has:<item>")
+            },
+            "--record" => match args.next() {
+                Option::Some(file) => commands = Option::Some(file),
+                Option::None => usage("--record needs a file of commands")
+            },
+            "--test" => match args.next() {
+                Option::Some(file) => transcripts.push(file),
+                Option::None => usage("--test needs a transcript file")
+            },
+            "--check" => match args.next() {
+                Option::Some(file) => check(&file),
+                Option::None => usage("--check needs a file")
+            },
+            "--export" => match args.next() {
+                Option::Some(file) => export = Option::Some(file),
+                Option::None => usage("--export needs a file")
+            },
+            _ => usage(&format!("Unknown option '{}'", arg))
+        }
+    }
+
+    let mut options = app::Options::new();
+    if generate {
+        let seed = seed.unwrap_or_else(Rng::time_seed);
+        let text = toml::to_string(&generator::generate(seed)).unwrap_or_default();
+        if let Option::Some(file) = export {
+            if let Err(e) = fs::write(&file, text) {
+                eprintln!("Cannot write {}: {}", file, e);
+                process::exit(1);
+            }
+            return;
+        }
+        options = options.set_world(&text).set_seed(seed);
+    }
+    else if let Option::Some(file) = world {
+        match fs::read_to_string(&file) {
+            Ok(text) => options = options.set_world(&text),
+            Err(e) => usage(&format!("Cannot read {}: {}", file, e))
+        }
+    }
+    else if export.is_some() {
+        usage("--export only works with --generate");
+    }
+    if let Option::Some(seed) = seed {
+        options = options.set_seed(seed);
+    }
+    if let Option::Some(id) = locale {
+        Locale::load(&id).unwrap_or_else(|e| usage(&e));
+        options = options.set_locale(&id);
+    }
+
+    if let Option::Some(goal) = goal {
+        solve(&options, &goal);
+    }
+    if let Option::Some(file) = commands {
+        record(&options, &file);
+    }
+    if !transcripts.is_empty() {
+        test(&options, &transcripts);
+    }
+
     let console = console::Console::new();
-    app::run(console);
+    app::run_with(console, options);
 }
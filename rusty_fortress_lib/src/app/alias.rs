@@ -0,0 +1,218 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::tokenizer;
+use crate::universe::data;
+
+// Words the player made up for other commands. An alias stands for the first word of a line,
+// as "ol" for "open lid", and a macro for a whole line played as several commands, as
+// "loot" for "take all; open chest". Either can use others, but never itself
+#[derive(Clone, Default)]
+pub struct Aliases {
+    aliases: Vec<(String, String)>,
+    macros: Vec<(String, Vec<String>)>
+}
+
+impl Aliases {
+
+    pub fn new() -> Aliases {
+        Aliases { aliases: Vec::new(), macros: Vec::new() }
+    }
+
+    fn name(text: &str) -> Option<String> {
+        match tokenizer::tokenize(text, message::ignore_accents()).as_slice() {
+            [token] if !token.literal => Option::Some(token.text.clone()),
+            _ => Option::None
+        }
+    }
+
+    // Reads "name = command" and makes the name an alias, or stops being one when nothing
+    // follows the equals sign. Without a definition it lists the aliases
+    pub fn define_alias(&mut self, text: &str) -> String {
+        self.define(text, "alias", |aliases, name, command| {
+            aliases.aliases.retain(|(n, _)| *n != name);
+            if !command.is_empty() {
+                aliases.aliases.push((name, command.to_string()));
+            }
+        })
+    }
+
+    // Same as define_alias for "name = command; command; ..."
+    pub fn define_macro(&mut self, text: &str) -> String {
+        self.define(text, "macro", |aliases, name, commands| {
+            aliases.macros.retain(|(n, _)| *n != name);
+            let commands: Vec<String> = commands.split(';').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+            if !commands.is_empty() {
+                aliases.macros.push((name, commands));
+            }
+        })
+    }
+
+    fn define(&mut self, text: &str, kind: &str, set: fn(&mut Aliases, String, &str)) -> String {
+        if text.trim().is_empty() {
+            return self.list();
+        }
+        let (name, command) = match text.split_once('=').map(|(n, c)| (Aliases::name(n), c.trim())) {
+            Option::Some((Option::Some(name), command)) => (name, command),
+            _ => return message::text(&format!("alias.invalid_{}", kind), &Args::new())
+        };
+        let previous = self.clone();
+        set(self, name.clone(), command);
+        if command.is_empty() {
+            return message::named("alias.removed", &name);
+        }
+        // A definition using itself is turned down before it is ever played
+        if let Err(e) = self.expand(&name) {
+            *self = previous;
+            return e;
+        }
+        message::text("alias.defined", &Args::new().set_name(&name).set("command", command))
+    }
+
+    fn list(&self) -> String {
+        if self.aliases.is_empty() && self.macros.is_empty() {
+            return message::text("alias.none", &Args::new());
+        }
+        let aliases = self.aliases.iter().map(|(name, command)| format!("{} = {}", name, command));
+        let macros = self.macros.iter().map(|(name, commands)| format!("{} = {}", name, commands.join("; ")));
+        aliases.chain(macros).collect::<Vec<String>>().join("\n")
+    }
+
+    // The commands to play for a line typed, which is the line itself if no alias is used
+    pub fn expand(&self, line: &str) -> Result<Vec<String>, String> {
+        self.expand_from(line, &mut Vec::new())
+    }
+
+    // Expands the line, knowing the aliases already being expanded to stop going round in circles
+    fn expand_from(&self, line: &str, used: &mut Vec<String>) -> Result<Vec<String>, String> {
+        let tokens = tokenizer::tokenize(line, message::ignore_accents());
+        let words = tokens.iter().map(|t| t.text.clone()).collect::<Vec<String>>().join(" ");
+        let (name, expanded) = if let Option::Some((name, commands)) = self.macros.iter().find(|(name, _)| *name == words) {
+            (name, commands.clone())
+        }
+        else {
+            let first = tokens.first().filter(|t| !t.literal);
+            match first.and_then(|t| self.aliases.iter().find(|(name, _)| *name == t.text).map(|alias| (t, alias))) {
+                Option::Some((token, (name, command))) => (name, vec!(format!("{}{}", command, &line[token.span.end..]))),
+                Option::None => return Ok(vec!(line.to_string()))
+            }
+        };
+        if used.contains(name) {
+            return Err(message::named("alias.cycle", name));
+        }
+        used.push(name.clone());
+        let mut lines = Vec::new();
+        for command in expanded.iter() {
+            lines.append(&mut self.expand_from(command, used)?);
+        }
+        used.pop();
+        Ok(lines)
+    }
+
+    // Adds the aliases read, replacing those with the same name
+    pub fn merge(&mut self, other: &Aliases) {
+        for (name, command) in other.aliases.iter() {
+            self.aliases.retain(|(n, _)| n != name);
+            self.aliases.push((name.clone(), command.clone()));
+        }
+        for (name, commands) in other.macros.iter() {
+            self.macros.retain(|(n, _)| n != name);
+            self.macros.push((name.clone(), commands.clone()));
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Aliases {
+        let empty = Table::new();
+        let section = |key: &str| table.get(key).and_then(|v| v.as_table()).unwrap_or(&empty);
+        Aliases {
+            aliases: section("aliases").iter().filter_map(|(n, c)| c.as_str().map(|c| (n.clone(), c.to_string()))).collect(),
+            macros: section("macros").iter().map(|(n, _)| (n.clone(), data::get_str_list(section("macros"), n))).collect()
+        }
+    }
+
+    pub fn to_data(&self, table: &mut Table) {
+        if !self.aliases.is_empty() {
+            let aliases = self.aliases.iter().map(|(n, c)| (n.clone(), Value::String(c.clone()))).collect();
+            table.insert(String::from("aliases"), Value::Table(aliases));
+        }
+        if !self.macros.is_empty() {
+            let macros = self.macros.iter().map(|(n, c)| (n.clone(), data::str_list(c))).collect();
+            table.insert(String::from("macros"), Value::Table(macros));
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(aliases: &Aliases, line: &str) -> Vec<String> {
+        aliases.expand(line).unwrap()
+    }
+
+    #[test]
+    fn an_alias_stands_for_the_first_words() {
+        let mut aliases = Aliases::new();
+        assert_eq!(aliases.define_alias("ol = open lid"), "ol now stands for open lid");
+        aliases.define_alias("x = look");
+        assert_eq!(expanded(&aliases, "ol"), vec!("open lid"));
+        assert_eq!(expanded(&aliases, "x rusty key"), vec!("look rusty key"));
+        assert_eq!(expanded(&aliases, "take ol"), vec!("take ol"));
+        assert_eq!(aliases.define_alias("ol ="), "ol no longer stands for anything");
+        assert_eq!(expanded(&aliases, "ol"), vec!("ol"));
+    }
+
+    #[test]
+    fn aliases_and_macros_use_others() {
+        let mut aliases = Aliases::new();
+        aliases.define_alias("t = take");
+        aliases.define_alias("tk = t key");
+        aliases.define_macro("loot = tk; open chest");
+        aliases.define_macro("raid = loot; t all");
+        assert_eq!(expanded(&aliases, "tk"), vec!("take key"));
+        assert_eq!(expanded(&aliases, "raid"), vec!("take key", "open chest", "take all"));
+    }
+
+    #[test]
+    fn an_alias_standing_for_itself_is_turned_down() {
+        let mut aliases = Aliases::new();
+        assert_eq!(aliases.define_alias("look = look around"), "look ends up standing for itself");
+        assert_eq!(expanded(&aliases, "look"), vec!("look"));
+        aliases.define_alias("a = b north");
+        assert_eq!(aliases.define_alias("b = a south"), "b ends up standing for itself");
+        assert_eq!(expanded(&aliases, "a"), vec!("b north"));
+        aliases.define_macro("m = take all; n");
+        assert_eq!(aliases.define_alias("n = m"), "n ends up standing for itself");
+        assert_eq!(expanded(&aliases, "m"), vec!("take all", "n"));
+    }
+
+    #[test]
+    fn a_macro_standing_for_itself_is_turned_down() {
+        let mut aliases = Aliases::new();
+        aliases.define_macro("loot = take all; open chest");
+        assert_eq!(aliases.define_macro("loot = take all; loot"), "loot ends up standing for itself");
+        assert_eq!(expanded(&aliases, "loot"), vec!("take all", "open chest"));
+    }
+
+    #[test]
+    fn a_saved_cycle_fails_when_played() {
+        let table = "[aliases]\na = \"b\"\nb = \"a\"\n".parse::<Value>().unwrap();
+        let aliases = Aliases::from_data(table.as_table().unwrap());
+        assert_eq!(aliases.expand("a"), Err(String::from("a ends up standing for itself")));
+    }
+
+    #[test]
+    fn aliases_are_saved() {
+        let mut aliases = Aliases::new();
+        aliases.define_alias("ol = open lid");
+        aliases.define_macro("loot = take all; open chest");
+        let mut table = Table::new();
+        aliases.to_data(&mut table);
+        let read = Aliases::from_data(&table);
+        assert_eq!(expanded(&read, "ol"), vec!("open lid"));
+        assert_eq!(expanded(&read, "loot"), vec!("take all", "open chest"));
+    }
+
+}
@@ -1,28 +1,483 @@
-use crate::app::command::DefaultCommandHandler;
+use std::cell::RefCell;
+use std::fs;
+use std::io::Write;
+use toml::Value;
+use crate::app::alias::Aliases;
+use crate::app::command::Command;
 use crate::app::command::HandleCommand;
-use crate::app::parser::Keyword;
+use crate::app::help::Help;
+use crate::app::history::History;
+use crate::app::locale::DEFAULT_LOCALE;
+use crate::app::locale::Locale;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::meta::Meta;
+use crate::app::meta::MetaCommand;
+use crate::app::meta::Registry;
 use crate::app::parser::StateMachine;
+use crate::app::style::Style;
+use crate::app::style::StyledLine;
+use crate::universe::score::Outcome;
+use crate::universe::world::World;
+
+const DEFAULT_WORLD: &str = include_str!("../../resources/worlds/fortress.toml");
+const DEFAULT_SAVE: &str = "rusty_fortress";
+const DEFAULT_TRANSCRIPT: &str = "rusty_fortress";
 
 pub trait InOut {
     fn read_line(&self) -> String;
     fn write_line(&self, line: String);
+
+    // Writes a line made of styled spans. This default is the plain-text adapter: front-ends
+    // without styles only implement write_line and get the text
+    fn write_styled(&self, line: StyledLine) {
+        self.write_line(line.text())
+    }
+
+    // True once there is nothing more to read, as at the end of a script
+    fn has_ended(&self) -> bool {
+        false
+    }
+}
+
+impl<T: InOut> InOut for &T {
+    fn read_line(&self) -> String {
+        (*self).read_line()
+    }
+
+    fn write_line(&self, line: String) {
+        (*self).write_line(line)
+    }
+
+    fn write_styled(&self, line: StyledLine) {
+        (*self).write_styled(line)
+    }
+
+    fn has_ended(&self) -> bool {
+        (*self).has_ended()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Options {
+    seed: Option<u64>,
+    world: Option<String>,
+    locale: Option<String>
+}
+
+impl Options {
+
+    pub fn new() -> Options {
+        Options {
+            seed: Option::None,
+            world: Option::None,
+            locale: Option::None
+        }
+    }
+
+    pub fn set_seed(mut self, seed: u64) -> Options {
+        self.seed = Option::Some(seed);
+        self
+    }
+
+    // Plays the given world file instead of the built-in fortress
+    pub fn set_world(mut self, text: &str) -> Options {
+        self.world = Option::Some(text.to_string());
+        self
+    }
+
+    // Plays in the locale with that id instead of English
+    pub fn set_locale(mut self, id: &str) -> Options {
+        self.locale = Option::Some(id.to_string());
+        self
+    }
+
+}
+
+// The player side of a game, copying what is read and written to a transcript file while one
+// is being written. The aliases made up by the player last as long as the session
+struct Session<T: InOut> {
+    in_out: T,
+    transcript: RefCell<Option<fs::File>>,
+    aliases: RefCell<Aliases>
+}
+
+impl<T: InOut> Session<T> {
+
+    fn new(in_out: T) -> Session<T> {
+        Session { in_out, transcript: RefCell::new(Option::None), aliases: RefCell::new(Aliases::new()) }
+    }
+
+    fn record(&self, line: &str) {
+        if let Option::Some(file) = self.transcript.borrow_mut().as_mut() {
+            // A transcript that cannot be written is not worth stopping the game for
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+
+    // Starts writing a transcript in the file named, or stops the one being written
+    fn toggle_transcript(&self, args: &[String]) -> String {
+        if self.transcript.borrow_mut().take().is_some() {
+            return message::text("meta.transcript_off", &Args::new());
+        }
+        let file = format!("{}.txt", if args.is_empty() { DEFAULT_TRANSCRIPT.to_string() } else { args.join(" ") });
+        match fs::File::create(&file) {
+            Ok(created) => {
+                *self.transcript.borrow_mut() = Option::Some(created);
+                message::text("meta.transcript_on", &Args::new().set("file", &file))
+            },
+            Err(e) => message::text("meta.transcript_failed", &Args::new().set("error", &e.to_string()))
+        }
+    }
+
+}
+
+impl<T: InOut> InOut for Session<T> {
+    fn read_line(&self) -> String {
+        let line = self.in_out.read_line();
+        if !self.in_out.has_ended() {
+            self.record(&format!("> {}", line));
+        }
+        line
+    }
+
+    fn write_line(&self, line: String) {
+        self.record(&line);
+        self.in_out.write_line(line)
+    }
+
+    fn write_styled(&self, line: StyledLine) {
+        self.record(&line.text());
+        self.in_out.write_styled(line)
+    }
+
+    fn has_ended(&self) -> bool {
+        self.in_out.has_ended()
+    }
+}
+
+fn save_file(args: &[String]) -> String {
+    let name = if args.is_empty() { DEFAULT_SAVE.to_string() } else { args.join(" ") };
+    format!("{}.sav", name)
+}
+
+// Saves the world along with the aliases of the player
+fn save(world: &World, aliases: &Aliases, file: &str) -> String {
+    let mut value = world.to_data();
+    if let Value::Table(table) = &mut value {
+        aliases.to_data(table);
+    }
+    let text = match toml::to_string(&value) {
+        Ok(text) => text,
+        Err(e) => return message::text("game.save_failed", &Args::new().set("error", &e.to_string()))
+    };
+    match fs::write(file, text) {
+        Ok(_) => message::text("game.saved", &Args::new().set("file", file)),
+        Err(e) => message::text("game.save_failed", &Args::new().set("error", &e.to_string()))
+    }
+}
+
+fn restore(file: &str) -> Result<(World, Aliases), String> {
+    match fs::read_to_string(file) {
+        Ok(text) => {
+            let world = World::load(&text)?;
+            message::set_overrides(world.messages());
+            let aliases = text.parse::<Value>().ok().and_then(|v| v.as_table().map(Aliases::from_data)).unwrap_or_default();
+            Ok((world, aliases))
+        },
+        Err(e) => Err(message::text("game.restore_failed", &Args::new().set("error", &e.to_string())))
+    }
+}
+
+// The locale chosen in the options, English by default
+pub fn locale(options: &Options) -> Result<Locale, String> {
+    Locale::load(options.locale.as_deref().unwrap_or(DEFAULT_LOCALE))
+}
+
+// Loads the world chosen in the options, ready to be played in the locale chosen with the
+// messages of the world
+pub fn load(options: &Options) -> Result<World, String> {
+    message::set_locale(&locale(options)?);
+    let mut world = World::load(options.world.as_deref().unwrap_or(DEFAULT_WORLD))?;
+    message::set_overrides(world.messages());
+    if let Option::Some(seed) = options.seed {
+        world.reseed(seed);
+    }
+    Ok(world)
+}
+
+// Resolves a command, or the line as a conversation choice when there is no command or a
+// conversation is going on. Only the commands changing the world take a turn, and for those
+// the world as it was before is returned as well
+pub fn play(world: &mut World, command: Option<&Command>, line: &str) -> (Vec<String>, Option<Value>) {
+    let before = world.clone();
+    match play_from(world, command, line, &before) {
+        (lines, true) => (lines, Option::Some(before.to_data())),
+        (lines, false) => (lines, Option::None)
+    }
+}
+
+// Same as play, for callers already holding the world as it was before the command. Tells
+// whether a turn went by
+pub fn play_from(world: &mut World, command: Option<&Command>, line: &str, before: &World) -> (Vec<String>, bool) {
+    let mut lines = vec!(match command {
+        Option::Some(command) if !world.in_conversation() => world.resolve_command(command),
+        _ => world.choose(line)
+    });
+    if *world == *before {
+        return (lines, false);
+    }
+    lines.append(&mut world.advance());
+    (lines, true)
+}
+
+// Writes what the game says about itself rather than the world
+fn system<T: InOut>(in_out: &T, text: String) {
+    in_out.write_styled(StyledLine::styled(Style::System, &text));
+}
+
+// A response of the world, styled: the room name heading a description as a title and the
+// names of the things around as objects. A command not understood is an error
+fn styled(world: &World, text: &str, understood: bool) -> StyledLine {
+    if !understood {
+        return StyledLine::styled(Style::Error, text);
+    }
+    let name = world.room_name();
+    let line = match text.strip_prefix(name.as_str()) {
+        Option::Some(rest) if rest.is_empty() || rest.starts_with('\n') => StyledLine::styled(Style::Title, &name).push(Style::Plain, rest),
+        _ => StyledLine::plain(text)
+    };
+    line.mark(&world.names_in_scope(), Style::Object)
+}
+
+// A meta command's message followed by a look around, as after restoring a game
+fn system_then_look(world: &World, id: &str) -> StyledLine {
+    let mut line = StyledLine::styled(Style::System, &format!("{}\n", message::text(id, &Args::new())));
+    line.spans.append(&mut styled(world, &world.look(), true).spans);
+    line
+}
+
+fn start<T: InOut>(in_out: &T, options: &Options) -> Option<World> {
+    match load(options) {
+        Ok(mut world) => {
+            in_out.write_styled(StyledLine::styled(Style::Title, &world.title()));
+            let entered = world.enter();
+            in_out.write_styled(styled(&world, &entered, true));
+            Option::Some(world)
+        },
+        Err(e) => {
+            in_out.write_styled(StyledLine::styled(Style::Error, &e));
+            Option::None
+        }
+    }
+}
+
+// Asks the dead player what to do next. Returns the world to keep playing, if any
+fn game_over<T: InOut>(in_out: &Session<T>, options: &Options, registry: &Registry) -> Option<World> {
+    in_out.write_styled(StyledLine::styled(Style::Emphasis, &message::text("game.died", &Args::new())));
+    loop {
+        system(in_out, message::text("game.died_prompt", &Args::new()));
+        let line = in_out.read_line();
+        if in_out.has_ended() {
+            return Option::None;
+        }
+        match registry.parse(&line).map(|command| command.meta) {
+            Option::Some(Meta::Restore) => match restore(&save_file(&[])) {
+                Ok((world, aliases)) => {
+                    in_out.aliases.borrow_mut().merge(&aliases);
+                    in_out.write_styled(styled(&world, &world.look(), true));
+                    return Option::Some(world);
+                },
+                Err(e) => in_out.write_styled(StyledLine::styled(Style::Error, &e))
+            },
+            Option::Some(Meta::Restart) => return start(in_out, options),
+            Option::Some(Meta::Quit) => return Option::None,
+            _ => {}
+        }
+    }
+}
+
+fn game_end<T: InOut>(in_out: &T, world: &World, outcome: Outcome) {
+    let (title, message) = match outcome {
+        Outcome::Victory(message) => ("game.won", message),
+        Outcome::Defeat(message) => ("game.lost", message)
+    };
+    in_out.write_styled(StyledLine::styled(Style::Emphasis, &message::text(title, &Args::new())));
+    if !message.is_empty() {
+        in_out.write_line(message);
+    }
+    system(in_out, world.summary());
+}
+
+// The command to play again: the last one understood, or the one with the number given
+fn recall(history: &History, args: &[String]) -> Result<(String, Command), String> {
+    let entry = match args.first() {
+        Option::Some(number) => number.parse::<usize>().ok().and_then(|n| history.get(n))
+            .ok_or_else(|| message::text("again.no_such", &Args::new().set("number", number)))?,
+        Option::None => history.last().ok_or_else(|| message::text("again.nothing", &Args::new()))?
+    };
+    Ok(entry.clone())
+}
+
+// Asks whether to go ahead with a command losing the game being played
+fn confirm<T: InOut>(in_out: &T, registry: &Registry, meta: &Meta) -> bool {
+    system(in_out, message::text(&format!("meta.confirm_{}", meta.id()), &Args::new()));
+    let answer = in_out.read_line();
+    !in_out.has_ended() && registry.is_yes(&answer)
+}
+
+// Runs a meta command, none of them taking a turn. Returns whether the game goes on
+fn run_meta<T: InOut>(in_out: &Session<T>, options: &Options, registry: &Registry, help: &Help,
+                      command: &MetaCommand, world: &mut World, snapshots: &mut Vec<Value>) -> bool {
+    if command.meta.is_destructive() && !confirm(in_out, registry, &command.meta) {
+        system(in_out, message::text("meta.cancelled", &Args::new()));
+        return true;
+    }
+    match command.meta {
+        Meta::Help => system(in_out, help.answer(&command.args)),
+        // Reading a hint changes which one comes next, but takes no turn
+        Meta::Hint => system(in_out, world.hint()),
+        Meta::Quit => {
+            system(in_out, message::text("meta.quit", &Args::new()));
+            return false;
+        },
+        Meta::Save => system(in_out, save(world, &in_out.aliases.borrow(), &save_file(&command.args))),
+        Meta::Restore => match restore(&save_file(&command.args)) {
+            Ok((restored, aliases)) => {
+                *world = restored;
+                in_out.aliases.borrow_mut().merge(&aliases);
+                snapshots.clear();
+                in_out.write_styled(system_then_look(world, "game.restored"));
+            },
+            Err(e) => in_out.write_styled(StyledLine::styled(Style::Error, &e))
+        },
+        Meta::Restart => {
+            if let Option::Some(restarted) = start(in_out, options) {
+                *world = restarted;
+                snapshots.clear();
+            }
+        },
+        Meta::Undo => match snapshots.pop().map(|snapshot| World::from_data(&snapshot)) {
+            Option::Some(Ok(previous)) => {
+                *world = previous;
+                in_out.write_styled(system_then_look(world, "game.undone"));
+            },
+            Option::Some(Err(e)) => in_out.write_styled(StyledLine::styled(Style::Error, &e)),
+            Option::None => system(in_out, message::text("meta.nothing_to_undo", &Args::new()))
+        },
+        Meta::Transcript => system(in_out, in_out.toggle_transcript(&command.args)),
+        // Played in the game loop, which keeps the history
+        Meta::Again | Meta::History => {},
+        Meta::Alias => {
+            let answer = in_out.aliases.borrow_mut().define_alias(&command.rest);
+            system(in_out, answer)
+        },
+        Meta::Macro => {
+            let answer = in_out.aliases.borrow_mut().define_macro(&command.rest);
+            system(in_out, answer)
+        },
+        Meta::Verbose | Meta::Brief => system(in_out, world.set_verbose(command.meta == Meta::Verbose)),
+        Meta::Version => {
+            let args = Args::new().set("title", &world.title()).set("version", env!("CARGO_PKG_VERSION"));
+            system(in_out, message::text("meta.version", &args))
+        }
+    }
+    true
 }
 
 pub fn run<T: InOut>(in_out: T) {
-    let mut handler = DefaultCommandHandler::new();
-    let state_machine = StateMachine::build();
+    run_with(in_out, Options::new());
+}
+
+pub fn run_with<T: InOut>(in_out: T, options: Options) {
+    let in_out = Session::new(in_out);
+    let mut world = match start(&in_out, &options) {
+        Option::Some(world) => world,
+        Option::None => return
+    };
+    // Starting the game has already loaded the locale, so it cannot fail here
+    let locale = locale(&options).unwrap_or_else(|_| Locale::english());
+    let state_machine = StateMachine::from_locale(&locale);
+    let registry = Registry::from_locale(&locale);
+    let help = Help::from_locale(&locale);
+    let mut snapshots: Vec<Value> = Vec::new();
+    let mut history = History::new();
+
     let mut running = true;
     while running {
         let line = in_out.read_line();
-        if line.len() > 0 {
-            let command = state_machine.parse_line(&line);
-            //println!("{}", command);
-            
-            if let Option::Some(Keyword::Quit) = command.keyword {
-                running = false;
-            } 
-            let text = handler.resolve_command(&command);
-            in_out.write_line(text);
-        }
-    }
-}
\ No newline at end of file
+        if in_out.has_ended() {
+            break;
+        }
+        if !line.is_empty() {
+            // Aliases are expanded first, except in the line defining one
+            let lines = match registry.parse(&line).map(|command| command.meta) {
+                Option::Some(Meta::Alias) | Option::Some(Meta::Macro) => Ok(vec!(line)),
+                _ => in_out.aliases.borrow().expand(&line)
+            };
+            let lines = lines.unwrap_or_else(|e| {
+                in_out.write_styled(StyledLine::styled(Style::Error, &e));
+                Vec::new()
+            });
+            for line in lines {
+                // Meta commands come first, so they work the same in the middle of a conversation
+                let played = match registry.parse(&line) {
+                    // A command played again is played as it was typed the first time
+                    Option::Some(MetaCommand { meta: Meta::Again, args, .. }) => match recall(&history, &args) {
+                        Ok((line, command)) => {
+                            system(&in_out, message::text("again.playing", &Args::new().set("line", &line)));
+                            Option::Some((line, Option::Some(command)))
+                        },
+                        Err(e) => {
+                            in_out.write_styled(StyledLine::styled(Style::Error, &e));
+                            Option::None
+                        }
+                    },
+                    Option::Some(MetaCommand { meta: Meta::History, .. }) => {
+                        system(&in_out, history.list());
+                        Option::None
+                    },
+                    Option::Some(command) => {
+                        running = run_meta(&in_out, &options, &registry, &help, &command, &mut world, &mut snapshots);
+                        Option::None
+                    },
+                    // Conversation choices are understood by the world rather than the parser
+                    Option::None if world.in_conversation() => Option::Some((line.clone(), Option::None)),
+                    Option::None => Option::Some((line.clone(), Option::Some(state_machine.parse_line(&line))))
+                };
+                if let Option::Some((line, command)) = played {
+                    let understood = command.as_ref().map(|c| c.status).unwrap_or(true);
+                    let (lines, before) = play(&mut world, command.as_ref(), &line);
+                    for (idx, text) in lines.iter().enumerate() {
+                        in_out.write_styled(styled(&world, text, understood || idx > 0));
+                    }
+                    // Only the commands that changed the world succeeded, and may be played again
+                    if let Option::Some(before) = before {
+                        snapshots.push(before);
+                        if let Option::Some(command) = command {
+                            history.push(&line, &command);
+                        }
+                    }
+                }
+
+                if !running {
+                    break;
+                }
+                if let Option::Some(outcome) = world.outcome() {
+                    game_end(&in_out, &world, outcome);
+                    running = false;
+                    break;
+                }
+                if world.is_dead() {
+                    snapshots.clear();
+                    match game_over(&in_out, &options, &registry) {
+                        Option::Some(restarted) => world = restarted,
+                        Option::None => running = false
+                    }
+                    // What was left of a macro was meant for the game lost
+                    break;
+                }
+            }
+        }
+    }
+}
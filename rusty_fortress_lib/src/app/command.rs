@@ -1,25 +1,42 @@
 use std::fmt;
+use crate::app::message;
+use crate::app::message::Args;
 use crate::app::parser::Keyword;
+use crate::app::tokenizer::Span;
 
+#[derive(Clone)]
 pub struct Command {
     pub status: bool,
     pub keyword: Option<Keyword>,
-    pub objects: Vec<String>
+    pub objects: Vec<String>,
+    // Where each object is in the line typed
+    pub spans: Vec<Span>,
+    // Text given between quotes, as typed
+    pub literal: Option<String>,
+    // How many of the object, as in "take 3 arrows"
+    pub quantity: Option<u32>,
+    // Message id of what was wrong with a line the parser turned down, as a count too large
+    pub error: Option<String>
 }
 
 impl fmt::Display for Command {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        return if self.status {
+        if self.status {
             let kwd = self.keyword.as_ref().unwrap();
-            if self.objects.len() == 0 {
+            let result = if self.objects.is_empty() {
                 write!(f, "Keyword: {}", kwd)
             }
             else {
                 let mut text = String::from("");
                 self.objects.iter().for_each(|s| {
-                    text = if text == "" { format!("'{}'", s) } else { format!("{}, '{}'", text, s) }
+                    text = if text.is_empty() { format!("'{}'", s) } else { format!("{}, '{}'", text, s) }
                 });
                 write!(f, "Keyword: {} / Objects: {}", kwd, text)
+            };
+            result?;
+            match &self.literal {
+                Option::Some(literal) => write!(f, " / Literal: \"{}\"", literal),
+                Option::None => Ok(())
             }
         }
         else {
@@ -40,19 +57,23 @@ impl DefaultCommandHandler {
     }
 }
 
+impl Default for DefaultCommandHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HandleCommand for DefaultCommandHandler {
 
-    fn resolve_command(&mut self, command: &Command) -> String { 
+    fn resolve_command(&mut self, command: &Command) -> String {
+        if let Option::Some(error) = &command.error {
+            return message::text(error, &Args::new());
+        }
         match &command.keyword {
-            Option::None => String::from("Don't understand what you want to do?"),
-            Option::Some(keyword) => match keyword {
-                Keyword::GoThrough => String::from("Can't go there"),
-                Keyword::Help => String::from("Get a little help to my friends"),
-                Keyword::Open => String::from("Don't know how to open that"),
-                Keyword::OpenWith => String::from("Don't know how to open that"),
-                Keyword::Quit => String::from("Goodbye cruel world!"),
-                Keyword::Take => String::from("Can't take that"),
-                Keyword::View => String::from("Can't see anything")
+            Option::None => message::text("unknown", &Args::new()),
+            Option::Some(keyword) => {
+                let name = command.objects.first().cloned().unwrap_or_default();
+                message::text(&message::default_id(keyword), &Args::new().set_name(&name))
             }
         }
     }
@@ -0,0 +1,115 @@
+use crate::app::locale::Locale;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::meta::Meta;
+use crate::app::parser::Keyword;
+use crate::app::tokenizer;
+
+// What a verb takes after it
+enum Takes {
+    Nothing,
+    MaybeObject,
+    Object,
+    Quantity,
+    Literal
+}
+
+fn takes(keyword: &Keyword) -> Takes {
+    match keyword {
+        Keyword::Inventory | Keyword::Score => Takes::Nothing,
+        Keyword::View => Takes::MaybeObject,
+        Keyword::Take | Keyword::Drop | Keyword::Give => Takes::Quantity,
+        Keyword::Say => Takes::Literal,
+        _ => Takes::Object
+    }
+}
+
+// "<something>" in the locale being played
+fn placeholder(id: &str) -> String {
+    format!("<{}>", message::text(&format!("help.{}", id), &Args::new()))
+}
+
+// Answers help from the grammar of the locale being played: the verbs it knows and the ways
+// to use each of them
+pub struct Help {
+    verbs: Vec<(Keyword, Vec<String>)>,
+    meta: Vec<(Meta, Vec<String>)>,
+    locale: Locale
+}
+
+impl Help {
+
+    pub fn from_locale(locale: &Locale) -> Help {
+        // Debugging is for the authors of worlds rather than the players
+        let verbs = locale.verbs.iter().filter(|(k, phrases)| *k != Keyword::Debug && !phrases.is_empty()).cloned().collect();
+        let meta = locale.meta.iter().filter(|(_, phrases)| !phrases.is_empty()).cloned().collect();
+        Help { verbs, meta, locale: locale.clone() }
+    }
+
+    // The verbs and meta commands, or how to use the one asked about
+    pub fn answer(&self, args: &[String]) -> String {
+        if args.is_empty() {
+            return self.summary();
+        }
+        let topic = tokenizer::words(&args.join(" "), self.locale.ignore_accents);
+        let is = |phrases: &Vec<String>| phrases.iter().any(|p| tokenizer::words(p, self.locale.ignore_accents) == topic);
+        if let Option::Some((keyword, phrases)) = self.verbs.iter().find(|(_, phrases)| is(phrases)) {
+            return self.with_synonyms(self.verb_usage(keyword, &phrases[0]), phrases);
+        }
+        if let Option::Some((meta, phrases)) = self.meta.iter().find(|(_, phrases)| is(phrases)) {
+            return self.with_synonyms(meta_usage(meta, &phrases[0]), phrases);
+        }
+        message::named("help.unknown", &args.join(" "))
+    }
+
+    fn summary(&self) -> String {
+        let listed = |mut words: Vec<String>| {
+            words.sort();
+            words.join(", ")
+        };
+        let verbs = listed(self.verbs.iter().map(|(_, phrases)| phrases[0].clone()).collect());
+        let commands = listed(self.meta.iter().map(|(_, phrases)| phrases[0].clone()).collect());
+        [
+            message::text("help.verbs", &Args::new().set("verbs", &verbs)),
+            message::text("help.meta", &Args::new().set("commands", &commands)),
+            message::text("help.more", &Args::new())
+        ].join("\n")
+    }
+
+    fn verb_usage(&self, keyword: &Keyword, verb: &str) -> Vec<String> {
+        let object = format!("{} {}", verb, placeholder("something"));
+        let mut usage = match takes(keyword) {
+            Takes::Nothing => vec!(verb.to_string()),
+            Takes::MaybeObject => vec!(verb.to_string(), object),
+            Takes::Object => vec!(object),
+            Takes::Quantity => vec!(object, format!("{} {} {}", verb, placeholder("number"), placeholder("something"))),
+            Takes::Literal => vec!(format!("{} \"{}\"", verb, placeholder("text")))
+        };
+        if let Option::Some((_, words)) = self.locale.preposition(keyword) {
+            if let Option::Some(word) = words.first() {
+                usage.push(format!("{} {} {} {}", verb, placeholder("something"), word, placeholder("something")));
+            }
+        }
+        usage
+    }
+
+    fn with_synonyms(&self, usage: Vec<String>, phrases: &[String]) -> String {
+        let mut lines = vec!(message::text("help.usage", &Args::new()));
+        lines.extend(usage.into_iter().map(|u| format!("  {}", u)));
+        if phrases.len() > 1 {
+            lines.push(message::text("help.synonyms", &Args::new().set("words", &phrases[1..].join(", "))));
+        }
+        lines.join("\n")
+    }
+
+}
+
+fn meta_usage(meta: &Meta, command: &str) -> Vec<String> {
+    match meta {
+        Meta::Save | Meta::Restore | Meta::Transcript => vec!(command.to_string(), format!("{} {}", command, placeholder("name"))),
+        Meta::Alias => vec!(command.to_string(), format!("{} {} = {}", command, placeholder("name"), placeholder("command"))),
+        Meta::Macro => vec!(command.to_string(), format!("{} {} = {}; {}", command, placeholder("name"), placeholder("command"), placeholder("command"))),
+        Meta::Again => vec!(command.to_string(), format!("{} {}", command, placeholder("number")), format!("!{}", placeholder("number"))),
+        _ => vec!(command.to_string())
+    }
+}
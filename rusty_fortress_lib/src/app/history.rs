@@ -0,0 +1,43 @@
+use crate::app::command::Command;
+use crate::app::message;
+use crate::app::message::Args;
+
+// The game commands played in this session that changed the world, as typed and as parsed,
+// so they can be played again. Meta commands and those that failed or changed nothing are
+// not kept, so the numbers are those listed by the history command
+#[derive(Default)]
+pub struct History {
+    entries: Vec<(String, Command)>
+}
+
+impl History {
+
+    pub fn new() -> History {
+        History { entries: Vec::new() }
+    }
+
+    pub fn push(&mut self, line: &str, command: &Command) {
+        self.entries.push((line.to_string(), command.clone()));
+    }
+
+    pub fn last(&self) -> Option<&(String, Command)> {
+        self.entries.last()
+    }
+
+    // The command with that number, counting from 1
+    pub fn get(&self, number: usize) -> Option<&(String, Command)> {
+        number.checked_sub(1).and_then(|idx| self.entries.get(idx))
+    }
+
+    // The commands numbered, as "!<n>" plays them again
+    pub fn list(&self) -> String {
+        if self.entries.is_empty() {
+            return message::text("history.none", &Args::new());
+        }
+        self.entries.iter().enumerate()
+            .map(|(idx, (line, _))| message::text("history.entry", &Args::new().set("number", &(idx + 1).to_string()).set("line", line)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+}
@@ -0,0 +1,172 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::meta::Meta;
+use crate::app::parser::Keyword;
+use crate::universe::data;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+// Locale packs shipped with the game
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../../resources/locales/en.toml")),
+    ("es", include_str!("../../resources/locales/es.toml"))
+];
+
+// Verbs taking a second object after a preposition, and the keyword they become
+const JOINED: [(Keyword, Keyword); 4] = [
+    (Keyword::Open, Keyword::OpenWith),
+    (Keyword::Ask, Keyword::AskAbout),
+    (Keyword::Give, Keyword::GiveTo),
+    (Keyword::Attack, Keyword::AttackWith)
+];
+
+// Articles for names whose first word is one of the words given, or matches the letters the
+// name starts or the word ends with
+#[derive(Clone)]
+struct ArticleRule {
+    words: Vec<String>,
+    starting: String,
+    ending: String,
+    definite: Option<String>,
+    indefinite: Option<String>
+}
+
+impl ArticleRule {
+
+    fn matches(&self, name: &str) -> bool {
+        let word = name.split(' ').next().unwrap_or_default().to_lowercase();
+        let first = name.chars().next();
+        let last = word.chars().last();
+        self.words.contains(&word) ||
+            first.map(|c| self.starting.contains(c)).unwrap_or(false) ||
+            last.map(|c| self.ending.contains(c)).unwrap_or(false)
+    }
+
+}
+
+// The words of a language: verbs, prepositions, articles and the message catalogue
+#[derive(Clone)]
+pub struct Locale {
+    pub id: String,
+    pub name: String,
+    pub ignore: Vec<String>,
+    // Whether "abrir" matches "abrír", for players typing without accents
+    pub ignore_accents: bool,
+    pub verbs: Vec<(Keyword, Vec<String>)>,
+    pub prepositions: Vec<(Keyword, Keyword, Vec<String>)>,
+    pub meta: Vec<(Meta, Vec<String>)>,
+    // Words answering yes to a question, as when quitting
+    pub yes: Vec<String>,
+    pub messages: Vec<(String, String)>,
+    pub numbers: Vec<(String, u32)>,
+    definite: String,
+    indefinite: String,
+    article_rules: Vec<ArticleRule>
+}
+
+impl Locale {
+
+    pub fn ids() -> Vec<&'static str> {
+        LOCALES.iter().map(|(id, _)| *id).collect()
+    }
+
+    pub fn load(id: &str) -> Result<Locale, String> {
+        match LOCALES.iter().find(|(i, _)| *i == id) {
+            Option::Some((_, text)) => match text.parse::<Value>() {
+                Ok(Value::Table(table)) => Locale::from_data(&table),
+                Ok(_) => Err(format!("Invalid locale '{}'", id)),
+                Err(e) => Err(format!("Invalid locale '{}': {}", id, e))
+            },
+            Option::None => Err(format!("Unknown locale '{}', choose one of: {}", id, Locale::ids().join(", ")))
+        }
+    }
+
+    pub fn english() -> Locale {
+        Locale::load(DEFAULT_LOCALE).expect("The English locale must be valid")
+    }
+
+    pub fn from_data(table: &Table) -> Result<Locale, String> {
+        let empty = Table::new();
+        let section = |key: &str| table.get(key).and_then(|v| v.as_table()).unwrap_or(&empty);
+
+        let mut verbs = Vec::new();
+        for (id, phrases) in section("verbs").iter() {
+            let keyword = Keyword::from_id(id).ok_or_else(|| format!("Unknown verb '{}'", id))?;
+            let phrases = phrases.as_array().map(|list| list.iter().filter_map(|p| p.as_str()).map(|p| p.to_lowercase()).collect());
+            verbs.push((keyword, phrases.unwrap_or_default()));
+        }
+        let mut prepositions = Vec::new();
+        for (id, word) in section("prepositions").iter() {
+            let joined = Keyword::from_id(id).ok_or_else(|| format!("Unknown verb '{}'", id))?;
+            let (verb, _) = JOINED.iter().find(|(_, j)| *j == joined).ok_or_else(|| format!("Verb '{}' takes no preposition", id))?;
+            // One word or a list of them
+            let words = match word {
+                Value::Array(list) => list.iter().filter_map(|w| w.as_str()).map(|w| w.to_lowercase()).collect(),
+                _ => vec!(word.as_str().unwrap_or_default().to_lowercase())
+            };
+            prepositions.push((verb.clone(), joined, words));
+        }
+        let mut meta = Vec::new();
+        for (id, phrases) in section("meta").iter() {
+            let command = Meta::from_id(id).ok_or_else(|| format!("Unknown meta command '{}'", id))?;
+            let phrases = phrases.as_array().map(|list| list.iter().filter_map(|p| p.as_str()).map(|p| p.to_lowercase()).collect());
+            meta.push((command, phrases.unwrap_or_default()));
+        }
+        let numbers = section("numbers").iter()
+            .filter_map(|(word, n)| n.as_integer().map(|n| (word.to_lowercase(), n.max(0) as u32)))
+            .collect();
+        let messages = section("messages").iter()
+            .map(|(id, text)| (id.clone(), text.as_str().unwrap_or_default().to_string()))
+            .collect();
+
+        let articles = section("articles");
+        let article_rules = data::get_tables(articles, "rule").iter().map(|rule| ArticleRule {
+            words: data::get_str_list(rule, "words"),
+            starting: data::get_str_or(rule, "starting", ""),
+            ending: data::get_str_or(rule, "ending", ""),
+            definite: data::get_str(rule, "definite").ok(),
+            indefinite: data::get_str(rule, "indefinite").ok()
+        }).collect();
+
+        Ok(Locale {
+            id: data::get_str(table, "id")?,
+            name: data::get_str_or(table, "name", ""),
+            ignore: data::get_str_list(table, "ignore"),
+            ignore_accents: data::get_bool_or(table, "ignore_accents", false),
+            verbs,
+            prepositions,
+            meta,
+            yes: data::get_str_list(section("answers"), "yes"),
+            messages,
+            numbers,
+            definite: data::get_str_or(articles, "definite", ""),
+            indefinite: data::get_str_or(articles, "indefinite", ""),
+            article_rules
+        })
+    }
+
+    pub fn message(&self, id: &str) -> Option<&str> {
+        self.messages.iter().find(|(i, _)| i == id).map(|(_, text)| text.as_str())
+    }
+
+    // The prepositions a verb takes before its second object, if any
+    pub fn preposition(&self, verb: &Keyword) -> Option<(&Keyword, &[String])> {
+        self.prepositions.iter().find(|(v, _, _)| v == verb).map(|(_, joined, words)| (joined, words.as_slice()))
+    }
+
+    fn article(&self, name: &str, pick: fn(&ArticleRule) -> Option<&String>, default: &str) -> String {
+        let article = self.article_rules.iter().filter(|r| r.matches(name)).find_map(pick).map(|a| a.as_str()).unwrap_or(default);
+        if article.is_empty() { name.to_string() } else { format!("{} {}", article, name) }
+    }
+
+    // "the key"
+    pub fn with_definite(&self, name: &str) -> String {
+        self.article(name, |r| r.definite.as_ref(), &self.definite)
+    }
+
+    // "a key", "an apple"
+    pub fn with_indefinite(&self, name: &str) -> String {
+        self.article(name, |r| r.indefinite.as_ref(), &self.indefinite)
+    }
+
+}
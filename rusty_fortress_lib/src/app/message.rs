@@ -0,0 +1,235 @@
+use std::cell::RefCell;
+use toml::Value;
+use toml::value::Table;
+use crate::app::locale::DEFAULT_LOCALE;
+use crate::app::locale::Locale;
+use crate::app::parser::Keyword;
+
+thread_local! {
+    // The English messages, the ones every locale and world can replace
+    static ENGLISH: Locale = Locale::english();
+    // The locale being played, if not English
+    static LOCALE: RefCell<Option<Locale>> = const { RefCell::new(Option::None) };
+    // Messages of the world being played replacing those of the locale
+    static OVERRIDES: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn with_locale<R>(f: impl FnOnce(&Locale) -> R) -> R {
+    LOCALE.with(|locale| match &*locale.borrow() {
+        Option::Some(locale) => f(locale),
+        Option::None => ENGLISH.with(f)
+    })
+}
+
+// What goes into the placeholders of a message
+#[derive(Clone)]
+pub struct Args {
+    name: String,
+    count: i64,
+    values: Vec<(String, String)>
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Args {
+
+    pub fn new() -> Args {
+        Args { name: String::new(), count: 1, values: Vec::new() }
+    }
+
+    pub fn set_name(mut self, name: &str) -> Args {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn set_count(mut self, count: i64) -> Args {
+        self.count = count;
+        self
+    }
+
+    pub fn set(mut self, key: &str, value: &str) -> Args {
+        self.values.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    fn value(&self, key: &str) -> Option<String> {
+        match key {
+            "name" => Option::Some(self.name.clone()),
+            "the" => Option::Some(with_locale(|l| l.with_definite(&self.name))),
+            "a" => Option::Some(with_locale(|l| l.with_indefinite(&self.name))),
+            "count" => Option::Some(self.count.to_string()),
+            _ => match self.values.iter().find(|(k, _)| k == key) {
+                Option::Some((_, value)) => Option::Some(value.clone()),
+                // "{Name}" is "{name}" capitalised
+                Option::None if key.starts_with(char::is_uppercase) => self.value(&key.to_lowercase()).map(|v| capitalise(&v)),
+                Option::None => Option::None
+            }
+        }
+    }
+
+}
+
+pub fn capitalise(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Option::Some(first) => first.to_uppercase().chain(chars).collect(),
+        Option::None => String::new()
+    }
+}
+
+// Fills in the placeholders. Unknown ones are left as they are
+pub fn render(template: &str, args: &Args) -> String {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Option::Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Option::Some(end) => start + end,
+            Option::None => {
+                text.push_str(&rest[start..]);
+                return text;
+            }
+        };
+        let key = &rest[start + 1..end];
+        match (args.value(key), key.split_once('|')) {
+            (Option::Some(value), _) => text.push_str(&value),
+            (Option::None, Option::Some((one, many))) => text.push_str(if args.count == 1 { one } else { many }),
+            (Option::None, Option::None) => text.push_str(&rest[start..=end])
+        }
+        rest = &rest[end + 1..];
+    }
+    text.push_str(rest);
+    text
+}
+
+// Id of what a verb says when nothing handles it: "default.go_through" for GoThrough
+pub fn default_id(keyword: &Keyword) -> String {
+    format!("default.{}", keyword.id())
+}
+
+fn template(id: &str) -> Option<String> {
+    let overridden = OVERRIDES.with(|o| o.borrow().iter().find(|(i, _)| i == id).map(|(_, t)| t.clone()));
+    overridden
+        .or_else(|| with_locale(|l| l.message(id).map(|t| t.to_string())))
+        .or_else(|| ENGLISH.with(|l| l.message(id).map(|t| t.to_string())))
+}
+
+// The message with that id, with its placeholders filled in. An id missing from the English
+// messages is a mistake in the code: it panics in debug builds and shows as the id otherwise
+pub fn text(id: &str, args: &Args) -> String {
+    match template(id) {
+        Option::Some(template) => render(&template, args),
+        Option::None if cfg!(debug_assertions) => panic!("Unknown message '{}'", id),
+        Option::None => id.to_string()
+    }
+}
+
+// The message with that id about a thing
+pub fn named(id: &str, name: &str) -> String {
+    text(id, &Args::new().set_name(name))
+}
+
+// Reads the "messages" table of a world, checking every message replaces a built-in one
+pub fn from_data(table: &Table) -> Result<Vec<(String, String)>, String> {
+    let mut messages = Vec::new();
+    for (id, text) in table.get("messages").and_then(|m| m.as_table()).cloned().unwrap_or_default() {
+        if ENGLISH.with(|l| l.message(&id).is_none()) {
+            return Err(format!("Unknown message '{}'", id));
+        }
+        match text.as_str() {
+            Option::Some(text) => messages.push((id, text.to_string())),
+            Option::None => return Err(format!("Message '{}' must be a text", id))
+        }
+    }
+    Ok(messages)
+}
+
+pub fn to_data(messages: &[(String, String)], table: &mut Table) {
+    if !messages.is_empty() {
+        let messages = messages.iter().map(|(id, text)| (id.clone(), Value::String(text.clone()))).collect();
+        table.insert(String::from("messages"), Value::Table(messages));
+    }
+}
+
+// Name of a state (open, lit...) in the locale being played
+pub fn state(state: &str) -> String {
+    template(&format!("state.{}", state)).map(|t| render(&t, &Args::new())).unwrap_or_else(|| state.to_string())
+}
+
+// Whether the locale being played matches words regardless of their accents
+pub fn ignore_accents() -> bool {
+    with_locale(|l| l.ignore_accents)
+}
+
+// Plays the game in that locale from now on
+pub fn set_locale(locale: &Locale) {
+    LOCALE.with(|l| *l.borrow_mut() = if locale.id == DEFAULT_LOCALE { Option::None } else { Option::Some(locale.clone()) });
+}
+
+// Makes the messages of a world replace those of the locale from now on. Loading a world
+// leaves them alone, so only the world being played is heard
+pub fn set_overrides(messages: &[(String, String)]) {
+    OVERRIDES.with(|o| *o.borrow_mut() = messages.to_vec());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use super::*;
+    use crate::app::parser::KEYWORDS;
+
+    // The texts between quotes in the code that look like message ids, as "door.locked"
+    fn quoted_ids(dir: &Path, ids: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                quoted_ids(&path, ids);
+            }
+            else if path.extension().map(|e| e == "rs").unwrap_or(false) {
+                let code = fs::read_to_string(&path).unwrap();
+                let quoted = code.lines().flat_map(|line| line.split('"').skip(1).step_by(2).collect::<Vec<&str>>());
+                ids.extend(quoted
+                    .filter(|text| text.contains('.') && !text.starts_with('.') && !text.ends_with('.'))
+                    .filter(|text| text.chars().all(|c| c.is_ascii_lowercase() || c == '_' || c == '.'))
+                    .map(|text| text.to_string()));
+            }
+        }
+    }
+
+    // The ids put together in the code rather than written whole
+    fn built_ids() -> Vec<String> {
+        let mut ids: Vec<String> = ["unknown", "not_found", "too_dark"].iter().map(|id| id.to_string()).collect();
+        ids.extend(KEYWORDS.iter().map(default_id));
+        ids.extend(["open", "take", "go_through", "light", "extinguish", "talk", "attack", "pull", "push", "drop", "view", "say"]
+            .iter().map(|id| format!("missing.{}", id)));
+        ids.extend(["something", "number", "text", "name", "command"].iter().map(|id| format!("help.{}", id)));
+        ids.extend(["quit", "restart", "restore"].iter().map(|id| format!("meta.confirm_{}", id)));
+        ids.extend(["alias", "macro"].iter().map(|id| format!("alias.invalid_{}", id)));
+        ids
+    }
+
+    #[test]
+    fn every_message_used_is_in_every_locale() {
+        let mut ids = built_ids();
+        quoted_ids(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut ids);
+        ids.sort();
+        ids.dedup();
+        for id in Locale::ids() {
+            let locale = Locale::load(id).unwrap();
+            let missing: Vec<&String> = ids.iter().filter(|i| locale.message(i).is_none()).collect();
+            assert!(missing.is_empty(), "Messages missing from the locale '{}': {:?}", id, missing);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown message 'no such message'")]
+    fn an_unknown_message_is_a_mistake() {
+        text("no such message", &Args::new());
+    }
+
+}
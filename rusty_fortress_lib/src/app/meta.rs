@@ -0,0 +1,129 @@
+use crate::app::locale::Locale;
+use crate::app::tokenizer;
+
+// Commands about the game itself rather than the world. They never take a turn and are
+// understood before the game parser sees the line
+#[derive(Clone, PartialEq, Debug)]
+pub enum Meta {
+    Help,
+    Quit,
+    Save,
+    Restore,
+    Restart,
+    Undo,
+    Again,
+    History,
+    Alias,
+    Macro,
+    Hint,
+    Transcript,
+    Verbose,
+    Brief,
+    Version
+}
+
+const METAS: [Meta; 15] = [
+    Meta::Help, Meta::Quit, Meta::Save, Meta::Restore, Meta::Restart, Meta::Undo, Meta::Again,
+    Meta::History, Meta::Alias, Meta::Macro, Meta::Hint, Meta::Transcript, Meta::Verbose,
+    Meta::Brief, Meta::Version
+];
+
+impl Meta {
+
+    // Name in locale packs and message ids
+    pub fn id(&self) -> &'static str {
+        match self {
+            Meta::Help => "help",
+            Meta::Quit => "quit",
+            Meta::Save => "save",
+            Meta::Restore => "restore",
+            Meta::Restart => "restart",
+            Meta::Undo => "undo",
+            Meta::Again => "again",
+            Meta::History => "history",
+            Meta::Alias => "alias",
+            Meta::Macro => "macro",
+            Meta::Hint => "hint",
+            Meta::Transcript => "transcript",
+            Meta::Verbose => "verbose",
+            Meta::Brief => "brief",
+            Meta::Version => "version"
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Meta> {
+        METAS.iter().find(|m| m.id() == id).cloned()
+    }
+
+    // Whether the game being played is lost, so the player is asked first
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, Meta::Quit | Meta::Restart | Meta::Restore)
+    }
+
+}
+
+// A meta command and the words typed after it, as they were typed: "save castle" is Save
+// with "castle". The rest of the line is kept whole for those reading more than words
+#[derive(Clone, PartialEq, Debug)]
+pub struct MetaCommand {
+    pub meta: Meta,
+    pub args: Vec<String>,
+    pub rest: String
+}
+
+// The phrases of each meta command in the locale being played, and the words answering yes
+pub struct Registry {
+    phrases: Vec<(Meta, Vec<String>)>,
+    yes: Vec<String>,
+    ignore_accents: bool
+}
+
+impl Registry {
+
+    pub fn build() -> Registry {
+        Registry::from_locale(&Locale::english())
+    }
+
+    pub fn from_locale(locale: &Locale) -> Registry {
+        let words = |phrase: &str| tokenizer::tokenize(phrase, locale.ignore_accents).into_iter().map(|t| t.text).collect::<Vec<String>>();
+        let phrases = locale.meta.iter()
+            .flat_map(|(meta, phrases)| phrases.iter().map(move |phrase| (meta.clone(), phrase)))
+            .map(|(meta, phrase)| (meta, words(phrase)))
+            .filter(|(_, words)| !words.is_empty())
+            .collect();
+        Registry {
+            phrases,
+            yes: locale.yes.iter().map(|word| tokenizer::normalise(word, locale.ignore_accents)).collect(),
+            ignore_accents: locale.ignore_accents
+        }
+    }
+
+    // The meta command the line starts with, if any. The longest phrase matching wins
+    pub fn parse(&self, line: &str) -> Option<MetaCommand> {
+        // "!3" plays the third command of the session again, whatever the locale
+        if let Option::Some(number) = line.trim().strip_prefix('!') {
+            if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) {
+                return Option::Some(MetaCommand { meta: Meta::Again, args: vec!(number.to_string()), rest: number.to_string() });
+            }
+        }
+        let tokens = tokenizer::tokenize(line, self.ignore_accents);
+        let starts = |words: &Vec<String>| words.len() <= tokens.len() &&
+            words.iter().zip(tokens.iter()).all(|(word, token)| !token.literal && *word == token.text);
+        self.phrases.iter()
+            .filter(|(_, words)| starts(words))
+            .max_by_key(|(_, words)| words.len())
+            .map(|(meta, words)| MetaCommand {
+                meta: meta.clone(),
+                args: tokens[words.len()..].iter()
+                    .map(|t| if t.literal { t.text.clone() } else { t.span.source(line).to_string() })
+                    .collect(),
+                rest: line[tokens[words.len() - 1].span.end..].trim().to_string()
+            })
+    }
+
+    // Whether the answer to a question is yes. Anything else is no
+    pub fn is_yes(&self, answer: &str) -> bool {
+        tokenizer::tokenize(answer, self.ignore_accents).first().map(|t| self.yes.contains(&t.text)).unwrap_or(false)
+    }
+
+}
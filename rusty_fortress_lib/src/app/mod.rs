@@ -1,3 +1,14 @@
 pub mod parser;
+pub mod alias;
 pub mod command;
+pub mod help;
+pub mod history;
+pub mod locale;
+pub mod message;
+pub mod meta;
+pub mod script;
+pub mod solver;
+pub mod style;
+pub mod tokenizer;
+#[allow(clippy::module_inception)]
 pub mod app;
\ No newline at end of file
@@ -1,5 +1,9 @@
 use std::fmt;
 use crate::app::command::Command;
+use crate::app::locale::Locale;
+use crate::app::tokenizer;
+use crate::app::tokenizer::Span;
+use crate::app::tokenizer::Token;
 
 #[derive(Clone, PartialEq)]
 enum StateAction {
@@ -16,29 +20,84 @@ enum InputAction {
 
 #[derive(Clone, PartialEq)]
 pub enum Keyword {
-    Help,
-    Quit, 
     Open,
     OpenWith,
     View, 
     Take,
-    GoThrough
+    GoThrough,
+    Light,
+    Extinguish,
+    Inventory,
+    Talk,
+    Ask,
+    AskAbout,
+    Give,
+    GiveTo,
+    Attack,
+    AttackWith,
+    Score,
+    Pull,
+    Push,
+    Debug,
+    Say,
+    Drop
 }
 
 impl fmt::Display for Keyword {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Keyword::Help => write!(f, "Help"),
-            Keyword::Quit => write!(f, "Quit"),
             Keyword::Open => write!(f, "Open"),
             Keyword::OpenWith => write!(f, "OpenWith"),
             Keyword::View => write!(f, "View"),
             Keyword::Take => write!(f, "Take"),
-            Keyword::GoThrough => write!(f, "GoThrough")
+            Keyword::GoThrough => write!(f, "GoThrough"),
+            Keyword::Light => write!(f, "Light"),
+            Keyword::Extinguish => write!(f, "Extinguish"),
+            Keyword::Inventory => write!(f, "Inventory"),
+            Keyword::Talk => write!(f, "Talk"),
+            Keyword::Ask => write!(f, "Ask"),
+            Keyword::AskAbout => write!(f, "AskAbout"),
+            Keyword::Give => write!(f, "Give"),
+            Keyword::GiveTo => write!(f, "GiveTo"),
+            Keyword::Attack => write!(f, "Attack"),
+            Keyword::AttackWith => write!(f, "AttackWith"),
+            Keyword::Score => write!(f, "Score"),
+            Keyword::Pull => write!(f, "Pull"),
+            Keyword::Push => write!(f, "Push"),
+            Keyword::Debug => write!(f, "Debug"),
+            Keyword::Say => write!(f, "Say"),
+            Keyword::Drop => write!(f, "Drop")
         }
     }
 }
 
+pub const KEYWORDS: [Keyword; 21] = [
+    Keyword::Open, Keyword::OpenWith, Keyword::View, Keyword::Take, Keyword::GoThrough,
+    Keyword::Light, Keyword::Extinguish, Keyword::Inventory, Keyword::Talk, Keyword::Ask,
+    Keyword::AskAbout, Keyword::Give, Keyword::GiveTo, Keyword::Attack, Keyword::AttackWith,
+    Keyword::Score, Keyword::Pull, Keyword::Push, Keyword::Debug, Keyword::Say, Keyword::Drop
+];
+
+impl Keyword {
+
+    // Name in locale packs and message ids: "go_through" for GoThrough
+    pub fn id(&self) -> String {
+        let mut id = String::new();
+        for (idx, c) in self.to_string().chars().enumerate() {
+            if c.is_uppercase() && idx > 0 {
+                id.push('_');
+            }
+            id.extend(c.to_lowercase());
+        }
+        id
+    }
+
+    pub fn from_id(id: &str) -> Option<Keyword> {
+        KEYWORDS.iter().find(|k| k.id() == id).cloned()
+    }
+
+}
+
 #[derive(Clone, PartialEq)]
 pub enum OutputAction {
     None,
@@ -162,10 +221,10 @@ impl State {
     fn next_state(&self, text: &str) -> RuleResult {
         for rule in self.rules.iter() {
             if text == rule.input {
-                return self.clone_and_replace_output(&text, &rule.result);
+                return self.clone_and_replace_output(text, &rule.result);
             }
         }
-        self.clone_and_replace_output(&text, &self.default_rule.result)
+        self.clone_and_replace_output(text, &self.default_rule.result)
     }
 
 }
@@ -177,24 +236,43 @@ impl fmt::Display for State {
 }
 
 pub struct StateMachine {
-    states: Vec<State>    
+    states: Vec<State>,
+    // Words skipped wherever they are, like articles
+    ignore: Vec<String>,
+    ignore_accents: bool,
+    // Words for numbers, as in "take three arrows"
+    numbers: Vec<(String, u32)>
 }
 
 impl StateMachine {
 
     pub fn build() -> StateMachine {
-        let states = vec!(
+        StateMachine::from_locale(&Locale::english())
+    }
+
+    // Builds the states from the verb phrases of a locale. A phrase of several words goes
+    // through a state for each word but the last ("p_go" for "go through"), and a verb
+    // taking a second object goes to its own state waiting for the preposition ("i_open")
+    pub fn from_locale(locale: &Locale) -> StateMachine {
+        let mut phrases: Vec<(Vec<String>, Keyword, String)> = Vec::new();
+        for (keyword, list) in locale.verbs.iter() {
+            let target = match locale.preposition(keyword) {
+                Option::Some(_) => format!("i_{}", keyword.id()),
+                Option::None => String::from("default_intermediate_state")
+            };
+            for phrase in list.iter() {
+                let words: Vec<String> = phrase.split_whitespace().map(|w| tokenizer::normalise(w, locale.ignore_accents)).collect();
+                if !words.is_empty() {
+                    phrases.push((words, keyword.clone(), target.clone()));
+                }
+            }
+        }
+        let state_name = |words: &[String]| if words.is_empty() { String::from("initial_state") } else { format!("p_{}", words.join("_")) };
+
+        let mut states = vec!(
             State::build("initial_state")
-                .add_rule(StateRule::keyword_rule("help", "default_intermediate_state", Keyword::Help))
-                .add_rule(StateRule::keyword_rule("?", "default_intermediate_state", Keyword::Help))
-                .add_rule(StateRule::keyword_rule("exit", "default_intermediate_state", Keyword::Quit))
-                .add_rule(StateRule::keyword_rule("quit", "default_intermediate_state", Keyword::Quit))
-                .add_rule(StateRule::keyword_rule("view", "default_intermediate_state", Keyword::View))
-                .add_rule(StateRule::keyword_rule("take", "default_intermediate_state", Keyword::Take))
-                .add_rule(StateRule::keyword_rule("open", "i_open", Keyword::Open))
-                .add_rule(StateRule::rule("go").set_move_state("i_go").set_next_input())
                 .set_default_rule(StateRule::default_rule().set_move_state("unknown_state")),
-        
+
             State::build("unknown_state")
                 .set_default_rule(StateRule::error_rule()),
 
@@ -203,46 +281,111 @@ impl StateMachine {
                 .set_default_rule(StateRule::default_rule().set_next_input().set_object_output()),
 
             State::build("default_final_state")
-                .set_default_rule(StateRule::end_rule()),
-
-            State::build("i_open")
-                .add_rule(StateRule::rule("").set_move_state("f_open"))
-                .add_rule(StateRule::rule("with").set_move_state("i_openwith"))
-                .set_default_rule(StateRule::default_rule().set_next_input().set_object_output()),
-
-            State::build("i_openwith")
-                .set_default_rule(StateRule::keyword_rule("with", "default_intermediate_state", Keyword::OpenWith)),
+                .set_default_rule(StateRule::end_rule())
+        );
 
-            State::build("f_open")
-                .set_default_rule(StateRule::end_rule()),
+        // States for the first words of the phrases
+        for (words, _, _) in phrases.iter() {
+            for len in 1..words.len() {
+                let name = state_name(&words[..len]);
+                if states.iter().any(|s| s.name == name) {
+                    continue;
+                }
+                let parent = state_name(&words[..len - 1]);
+                if let Option::Some(state) = states.iter_mut().find(|s| s.name == parent) {
+                    state.rules.push(StateRule::rule(&words[len - 1]).set_move_state(&name).set_next_input());
+                }
+                states.push(State::build(&name).set_default_rule(StateRule::error_rule()));
+            }
+        }
+        // The last word gives the verb. When the phrase is also the start of a longer one
+        // ("pull" and "pull on") the verb is given by whatever does not continue it
+        for (words, keyword, target) in phrases.iter() {
+            let name = state_name(words);
+            if let Option::Some(state) = states.iter_mut().find(|s| s.name == name) {
+                state.default_rule = StateRule::default_rule().set_move_state(target).set_keyword_output(keyword.clone());
+                continue;
+            }
+            let parent = state_name(&words[..words.len() - 1]);
+            if let Option::Some(state) = states.iter_mut().find(|s| s.name == parent) {
+                state.rules.push(StateRule::keyword_rule(&words[words.len() - 1], target, keyword.clone()));
+            }
+        }
+        // The preposition before the second object turns the verb into another one
+        for (verb, joined, words) in locale.prepositions.iter() {
+            let joined_state = format!("i_{}", joined.id());
+            let mut state = State::build(&format!("i_{}", verb.id()))
+                .add_rule(StateRule::rule("").set_move_state("default_final_state"))
+                .set_default_rule(StateRule::default_rule().set_next_input().set_object_output());
+            for word in words.iter() {
+                state = state.add_rule(StateRule::rule(&tokenizer::normalise(word, locale.ignore_accents)).set_move_state(&joined_state));
+            }
+            states.push(state);
+            states.push(State::build(&joined_state)
+                .set_default_rule(StateRule::default_rule().set_move_state("default_intermediate_state").set_next_input().set_keyword_output(joined.clone())));
+        }
 
-            State::build("i_go")
-                .add_rule(StateRule::keyword_rule("through", "default_intermediate_state", Keyword::GoThrough))
-                .set_default_rule(StateRule::error_rule())
-        );
         StateMachine {
-            states: states
+            states,
+            ignore: locale.ignore.iter().map(|w| tokenizer::normalise(w, locale.ignore_accents)).collect(),
+            ignore_accents: locale.ignore_accents,
+            numbers: locale.numbers.iter().map(|(w, n)| (tokenizer::normalise(w, locale.ignore_accents), *n)).collect()
         }
     }
 
+    // The number a word stands for, if it is one. Digits too many to be counted are an error
+    fn number(&self, word: &str) -> Option<Result<u32, String>> {
+        if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+            return Option::Some(word.parse::<u32>().map_err(|_| String::from("quantity.too_large")));
+        }
+        self.numbers.iter().find(|(w, _)| w == word).map(|(_, n)| Ok(*n))
+    }
+
+    // Takes out the number the first object starts with, as "3" in "take 3 arrows", which is
+    // how many of it. A number anywhere else, or with no name after it, is part of a name
+    fn quantity(&self, output: &mut Vec<(OutputAction, Span)>) -> Option<Result<u32, String>> {
+        let first = output.iter().position(|(action, _)| matches!(action, OutputAction::Object(_)))?;
+        let number = match (&output[first].0, output.get(first + 1)) {
+            (OutputAction::Object(word), Option::Some((OutputAction::Object(_), _))) => self.number(word)?,
+            _ => return Option::None
+        };
+        output.remove(first);
+        Option::Some(number)
+    }
+
     fn find_by_name(&self, name: &str) -> Option<&State> {
         self.states.iter().find(|s| s.name == name)
     }
 
-    fn build_command(&self, output: Vec<OutputAction>) -> Command {
+    fn build_command(&self, mut output: Vec<(OutputAction, Span)>, literals: &[Token]) -> Command {
+        let (quantity, mut error) = match self.quantity(&mut output) {
+            Option::Some(Ok(quantity)) => (Option::Some(quantity), Option::None),
+            Option::Some(Err(error)) => (Option::None, Option::Some(error)),
+            Option::None => (Option::None, Option::None)
+        };
+        // A command carries a single text between quotes
+        let literal = literals.first().map(|t| t.text.clone());
+        if literals.len() > 1 {
+            error = error.or_else(|| Option::Some(String::from("literal.too_many")));
+        }
         let mut objs = Vec::new();
+        let mut spans = Vec::new();
         let mut err = false;
         let mut kwd: Option<Keyword> = Option::None;
 
-        let mut new_txt = String::from("");        
-        for output_state in output.iter() {
+        // Objects of several words are joined, along with their spans
+        let mut new_obj: Option<(String, Span)> = Option::None;
+        for (output_state, span) in output.iter() {
             if let OutputAction::Object(txt) = output_state {
-                new_txt = if new_txt == "" { txt.to_string() } else { format!("{} {}", new_txt, txt) }
+                new_obj = match new_obj {
+                    Option::Some((obj, obj_span)) => Option::Some((format!("{} {}", obj, txt), obj_span.join(span))),
+                    Option::None => Option::Some((txt.to_string(), *span))
+                }
             }
             else {
-                if new_txt != "" {
-                    objs.push(new_txt);
-                    new_txt = String::from("");
+                if let Option::Some((obj, obj_span)) = new_obj.take() {
+                    objs.push(obj);
+                    spans.push(obj_span);
                 }
                 if let OutputAction::Keyword(k) = output_state {
                     kwd = Option::Some(k.clone())
@@ -252,36 +395,48 @@ impl StateMachine {
                 }
             }
         }
-        if new_txt != "" {
-            objs.push(new_txt);
+        if let Option::Some((obj, obj_span)) = new_obj {
+            objs.push(obj);
+            spans.push(obj_span);
         }
 
+        // A line turned down is not played, whatever its verb
+        if error.is_some() {
+            err = true;
+            kwd = Option::None;
+        }
         Command {
             status: !err,
             objects: objs,
-            keyword: kwd
+            keyword: kwd,
+            spans,
+            literal,
+            quantity,
+            error
         }
     }
 
     pub fn parse_line(&self, text: &str) -> Command {
-        let words: Vec<String> = text.trim().split(' ')                       
-            .map(|w| w.to_lowercase())
-            .collect();
-        let mut words_iter = words.iter();
-        let empty_str = String::from(""); 
+        // The text between quotes goes apart, the rest of the words go through the states
+        let (literals, tokens): (Vec<Token>, Vec<Token>) = tokenizer::tokenize(text, self.ignore_accents).into_iter()
+            .filter(|t| t.literal || !self.ignore.contains(&t.text))
+            .partition(|t| t.literal);
+        let mut tokens_iter = tokens.iter();
+        // Past the last word the input is empty, at the end of the line
+        let end = Token { text: String::new(), span: Span { start: text.len(), end: text.len() }, literal: false };
 
         let mut running = true;
         let mut current_state = self.find_by_name("initial_state").unwrap();
-        let mut word = words_iter.next().unwrap_or(&empty_str);
-        let mut output: Vec<OutputAction> = Vec::new();
+        let mut token = tokens_iter.next().unwrap_or(&end);
+        let mut output: Vec<(OutputAction, Span)> = Vec::new();
         
         while running {
 
-            let applied_rule_result = current_state.next_state(&word);
+            let applied_rule_result = current_state.next_state(&token.text);
 
             match applied_rule_result.2 {
                 OutputAction::None => {},
-                _ => output.push(applied_rule_result.2)
+                _ => output.push((applied_rule_result.2, token.span))
             }
             
             match applied_rule_result.0 {
@@ -293,12 +448,58 @@ impl StateMachine {
             if running {
                 match applied_rule_result.1 {
                     InputAction::Keep => {},
-                    InputAction::Next => word = words_iter.next().unwrap_or(&empty_str)
+                    InputAction::Next => token = tokens_iter.next().unwrap_or(&end)
                 }
             }
 
         }
         
-        self.build_command(output)
+        self.build_command(output, &literals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_quantity_before_the_name() {
+        let command = StateMachine::build().parse_line("take 3 silver coins");
+        assert_eq!(command.quantity, Option::Some(3));
+        assert_eq!(command.objects, vec!("silver coins"));
+        let command = StateMachine::build().parse_line("give five coins to merchant");
+        assert_eq!(command.quantity, Option::Some(5));
+        assert_eq!(command.objects, vec!("coins", "merchant"));
+    }
+
+    #[test]
+    fn numbers_elsewhere_are_part_of_names() {
+        let command = StateMachine::build().parse_line("push button 3");
+        assert_eq!(command.quantity, Option::None);
+        assert_eq!(command.objects, vec!("button 3"));
+        let command = StateMachine::build().parse_line("open door 2 with key 7");
+        assert_eq!(command.quantity, Option::None);
+        assert_eq!(command.objects, vec!("door 2", "key 7"));
+        let command = StateMachine::build().parse_line("take 3");
+        assert_eq!(command.quantity, Option::None);
+        assert_eq!(command.objects, vec!("3"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn turns_down_counts_too_large() {
+        let command = StateMachine::build().parse_line("take 99999999999 coins");
+        assert!(!command.status);
+        assert!(command.keyword.is_none());
+        assert_eq!(command.error.as_deref(), Option::Some("quantity.too_large"));
+    }
+
+    #[test]
+    fn turns_down_more_than_one_quoted_text() {
+        let command = StateMachine::build().parse_line("say \"open\" \"sesame\"");
+        assert!(!command.status);
+        assert!(command.keyword.is_none());
+        assert_eq!(command.error.as_deref(), Option::Some("literal.too_many"));
+        assert_eq!(StateMachine::build().parse_line("say \"open sesame\"").literal.as_deref(), Option::Some("open sesame"));
+    }
+
+}
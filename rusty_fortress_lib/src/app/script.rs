@@ -0,0 +1,139 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use crate::app::app;
+use crate::app::app::InOut;
+use crate::app::app::Options;
+
+const COMMAND_MARK: &str = "> ";
+
+// Plays a list of commands and records everything the game writes, commands included
+pub struct Script {
+    commands: RefCell<VecDeque<String>>,
+    transcript: RefCell<Vec<String>>,
+    ended: Cell<bool>
+}
+
+impl Script {
+
+    pub fn new(commands: Vec<String>) -> Script {
+        Script {
+            commands: RefCell::new(commands.into_iter().collect()),
+            transcript: RefCell::new(Vec::new()),
+            ended: Cell::new(false)
+        }
+    }
+
+    // Reads one command per line, skipping blank lines and comments
+    pub fn from_file(file: &str) -> Result<Script, String> {
+        match fs::read_to_string(file) {
+            Ok(text) => Ok(Script::new(commands(&text))),
+            Err(e) => Err(format!("Cannot read {}: {}", file, e))
+        }
+    }
+
+    pub fn transcript(&self) -> Vec<String> {
+        self.transcript.borrow().clone()
+    }
+
+}
+
+impl InOut for Script {
+
+    fn read_line(&self) -> String {
+        match self.commands.borrow_mut().pop_front() {
+            Option::Some(line) => {
+                self.transcript.borrow_mut().push(format!("{}{}", COMMAND_MARK, line));
+                line
+            },
+            Option::None => {
+                self.ended.set(true);
+                String::new()
+            }
+        }
+    }
+
+    fn write_line(&self, line: String) {
+        self.transcript.borrow_mut().extend(line.lines().map(|l| l.to_string()));
+    }
+
+    fn has_ended(&self) -> bool {
+        self.ended.get()
+    }
+
+}
+
+fn commands(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+// Splits a transcript into the commands and the response to each one. The lines before the
+// first command are the response to starting the game
+fn responses(lines: &[String]) -> Vec<(String, Vec<String>)> {
+    let mut result = vec!((String::new(), Vec::new()));
+    for line in lines.iter().map(|line| line.trim_end()).filter(|line| !line.trim().is_empty()) {
+        match line.strip_prefix(COMMAND_MARK.trim_end()) {
+            Option::Some(command) => result.push((command.trim().to_string(), Vec::new())),
+            Option::None => result.last_mut().unwrap().1.push(line.to_string())
+        }
+    }
+    result
+}
+
+// Plays the script and returns the transcript of the game
+pub fn record(script: Script, options: Options) -> Vec<String> {
+    app::run_with(&script, options);
+    script.transcript()
+}
+
+// Plays the commands of a golden transcript and compares the responses with the expected
+// ones. Lines starting with "#" are comments, "@seed <number>" fixes the random numbers and
+// "@locale <id>" plays in that locale. Returns the differences found, none if the game behaves as expected
+pub fn test(text: &str, mut options: Options) -> Result<Vec<String>, String> {
+    let mut golden = Vec::new();
+    for line in text.lines() {
+        match line.strip_prefix("@seed") {
+            Option::Some(seed) => match seed.trim().parse::<u64>() {
+                Ok(seed) => options = options.set_seed(seed),
+                Err(_) => return Err(format!("Invalid seed '{}'", seed.trim()))
+            },
+            Option::None if line.starts_with("@locale") => options = options.set_locale(line["@locale".len()..].trim()),
+            Option::None if line.starts_with('#') => {},
+            Option::None => golden.push(line.to_string())
+        }
+    }
+    let expected = responses(&golden);
+    let commands = expected.iter().skip(1).map(|(command, _)| command.clone()).collect();
+    let actual = responses(&record(Script::new(commands), options));
+
+    let mut diffs = Vec::new();
+    for (idx, (command, lines)) in expected.iter().enumerate() {
+        let got = actual.get(idx).map(|(_, lines)| lines.clone()).unwrap_or_default();
+        if got != *lines {
+            let mut diff = vec!(if idx == 0 { String::from("At the start of the game:") } else { format!("After '{}':", command) });
+            lines.iter().for_each(|line| diff.push(format!("- {}", line)));
+            got.iter().for_each(|line| diff.push(format!("+ {}", line)));
+            diffs.push(diff.join("\n"));
+        }
+    }
+    Ok(diffs)
+}
+
+// Tests a golden transcript file. An "@world <file>" line plays that world, relative to
+// the transcript
+pub fn test_file(file: &str, mut options: Options) -> Result<Vec<String>, String> {
+    let text = fs::read_to_string(file).map_err(|e| format!("Cannot read {}: {}", file, e))?;
+    if let Option::Some(world) = text.lines().find_map(|line| line.strip_prefix("@world")) {
+        let path = Path::new(file).parent().unwrap_or_else(|| Path::new("")).join(world.trim());
+        let world = fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        options = options.set_world(&world);
+    }
+    let text: Vec<&str> = text.lines().filter(|line| !line.starts_with("@world")).collect();
+    test(&text.join("\n"), options)
+}
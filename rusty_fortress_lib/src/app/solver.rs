@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use toml::Value;
+use crate::app::app;
+use crate::app::locale::Locale;
+use crate::app::parser::Keyword;
+use crate::app::parser::StateMachine;
+use crate::universe::condition::Condition;
+use crate::universe::condition::Test;
+use crate::universe::data;
+use crate::universe::score::Outcome;
+use crate::universe::world::World;
+
+const VERBS: [Keyword; 9] = [
+    Keyword::Take, Keyword::Open, Keyword::GoThrough, Keyword::Light, Keyword::Extinguish,
+    Keyword::Pull, Keyword::Push, Keyword::Talk, Keyword::Attack
+];
+
+// Verbs taking a second object, and whether the carried object comes first or second
+const VERBS_WITH: [(Keyword, bool); 3] = [(Keyword::Open, false), (Keyword::Give, true), (Keyword::Attack, false)];
+
+const MAX_CHOICES: usize = 9;
+
+// What the solver looks for: a condition, or winning the game
+pub enum Goal {
+    Reach(Condition),
+    Victory
+}
+
+impl Goal {
+
+    // Either "victory" or a condition
+    pub fn from_data(text: &str) -> Result<Goal, String> {
+        if text == "victory" {
+            Ok(Goal::Victory)
+        }
+        else {
+            Ok(Goal::Reach(Condition::from_data(text)?))
+        }
+    }
+
+    pub fn to_data(&self) -> String {
+        match self {
+            Goal::Reach(condition) => condition.to_data(),
+            Goal::Victory => String::from("victory")
+        }
+    }
+
+    fn is_met(&self, world: &World) -> bool {
+        match self {
+            Goal::Reach(condition) => world.holds(condition),
+            Goal::Victory => matches!(world.outcome(), Option::Some(Outcome::Victory(_)))
+        }
+    }
+
+}
+
+// The words the solver plays in the locale of the game, so the walkthrough can be typed in
+// it. Verbs the locale has no words for are not tried
+struct Words {
+    say: Option<String>,
+    verbs: Vec<String>,
+    verbs_with: Vec<(String, String, bool)>
+}
+
+impl Words {
+
+    fn from_locale(locale: &Locale) -> Words {
+        let verb = |keyword: &Keyword| locale.verbs.iter().find(|(k, _)| k == keyword).and_then(|(_, phrases)| phrases.first()).cloned();
+        let preposition = |keyword: &Keyword| locale.preposition(keyword).and_then(|(_, words)| words.first()).cloned();
+        Words {
+            say: verb(&Keyword::Say),
+            verbs: VERBS.iter().filter_map(verb).collect(),
+            verbs_with: VERBS_WITH.iter()
+                .filter_map(|(keyword, carried_first)| Option::Some((verb(keyword)?, preposition(keyword)?, *carried_first)))
+                .collect()
+        }
+    }
+
+    // The commands worth trying on the world as it is now
+    fn candidates(&self, world: &World) -> Vec<String> {
+        if world.in_conversation() {
+            return (1..=MAX_CHOICES).map(|n| n.to_string()).collect();
+        }
+        let names = world.names_in_scope();
+        let carried = world.carried_names();
+        let mut lines: Vec<String> = match &self.say {
+            Option::Some(say) => world.passwords().iter().map(|words| format!("{} \"{}\"", say, words)).collect(),
+            Option::None => Vec::new()
+        };
+        for name in names.iter() {
+            self.verbs.iter().for_each(|verb| lines.push(format!("{} {}", verb, name)));
+            for (verb, joint, carried_first) in self.verbs_with.iter() {
+                for other in carried.iter().filter(|other| *other != name) {
+                    let (first, second) = if *carried_first { (other, name) } else { (name, other) };
+                    lines.push(format!("{} {} {} {}", verb, first, joint, second));
+                }
+            }
+        }
+        lines
+    }
+
+}
+
+// What keeps changing just because time goes by, the wounds of the player, as long as the
+// player stays alive, and what only changes how rooms are described. The fuel left in lights
+// goes too, so states only differing in it count as one and a walkthrough needing a light
+// to be spared may be missed
+const CLOCK_FIELDS: [&str; 4] = ["turn", "rng", "event", "verbose"];
+const THING_CLOCK_FIELDS: [&str; 3] = ["route_wait", "route_pos", "fuel"];
+
+// The state of the world leaving out what only changes with time, so waiting around doesn't
+// make it look new
+fn state_key(value: &Value) -> String {
+    let mut value = value.clone();
+    if let Option::Some(table) = value.as_table_mut() {
+        CLOCK_FIELDS.iter().for_each(|field| { table.remove(*field); });
+        if let Option::Some(player) = table.get_mut("player").and_then(|p| p.as_table_mut()) {
+            player.remove("hp");
+        }
+        let rooms = table.get_mut("room").and_then(|r| r.as_array_mut());
+        for room in rooms.into_iter().flatten().filter_map(|r| r.as_table_mut()) {
+            room.remove("visited");
+        }
+        let things = table.get_mut("thing").and_then(|t| t.as_array_mut());
+        for thing in things.into_iter().flatten().filter_map(|t| t.as_table_mut()) {
+            THING_CLOCK_FIELDS.iter().for_each(|field| { thing.remove(*field); });
+        }
+    }
+    value.to_string()
+}
+
+// A goal on something missing from the world would make the search run out for nothing
+fn check_goal(world: &Value, goal: &Goal) -> Result<(), String> {
+    let table = world.as_table().cloned().unwrap_or_default();
+    let (key, id) = match goal {
+        Goal::Reach(Condition { test: Test::Has(item), .. }) => ("thing", item),
+        Goal::Reach(Condition { test: Test::In(room), .. }) => ("room", room),
+        Goal::Reach(_) => return Ok(()),
+        Goal::Victory if data::get_tables(&table, "victory").is_empty() => return Err(String::from("The world has no victory")),
+        Goal::Victory => return Ok(())
+    };
+    if data::get_tables(&table, key).iter().any(|t| data::get_str_or(t, "id", "") == *id) {
+        Ok(())
+    }
+    else {
+        Err(format!("Unknown {} '{}'", key, id))
+    }
+}
+
+// Searches breadth first for the shortest list of commands taking the world to the goal,
+// playing every command for real on a copy of the world. States only differing in the
+// wounds of the player count as one, so a fight may take a command more than it could.
+// Gives up after exploring `limit` states. The commands are in the locale given
+pub fn solve(world: &World, goal: &Goal, locale: &Locale, limit: usize) -> Result<Option<Vec<String>>, String> {
+    if goal.is_met(world) {
+        return Ok(Option::Some(Vec::new()));
+    }
+    let state_machine = StateMachine::from_locale(locale);
+    let words = Words::from_locale(locale);
+    let start = world.to_data();
+    check_goal(&start, goal)?;
+    let mut seen = HashSet::new();
+    seen.insert(state_key(&start));
+    let mut queue = VecDeque::new();
+    queue.push_back((world.clone(), Vec::new()));
+
+    while let Option::Some((state, path)) = queue.pop_front() {
+        for line in words.candidates(&state).iter() {
+            let command = if state.in_conversation() { Option::None } else { Option::Some(state_machine.parse_line(line)) };
+            let mut played = state.clone();
+            if !app::play_from(&mut played, command.as_ref(), line, &state).1 {
+                continue;
+            }
+            let mut next_path: Vec<String> = path.clone();
+            next_path.push(line.clone());
+            if goal.is_met(&played) {
+                return Ok(Option::Some(next_path));
+            }
+            if played.is_dead() || played.outcome().is_some() {
+                continue;
+            }
+            if seen.len() < limit && seen.insert(state_key(&played.to_data())) {
+                queue.push_back((played, next_path));
+            }
+        }
+    }
+    Ok(Option::None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::message;
+
+    #[test]
+    fn solves_in_the_locale_played() {
+        let locale = Locale::load("es").unwrap();
+        message::set_locale(&locale);
+        let world = World::load(include_str!("../../resources/worlds/torre.toml")).unwrap();
+        let commands = solve(&world, &Goal::Victory, &locale, 1000).unwrap().unwrap();
+        assert_eq!(commands.first().map(|c| c.as_str()), Option::Some("abrir puerta"));
+        assert_eq!(commands.last().map(|c| c.as_str()), Option::Some("cruzar puerta"));
+    }
+
+}
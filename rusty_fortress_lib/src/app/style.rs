@@ -0,0 +1,140 @@
+use crate::app::tokenizer;
+
+// What a piece of output is, for front-ends to show each kind its own way
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Style {
+    Plain,
+    Title,
+    Emphasis,
+    Object,
+    Error,
+    System
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StyledSpan {
+    pub style: Style,
+    pub text: String
+}
+
+// A line of output made of spans of text, each with its style. It may hold several lines
+// of text, as a room description does
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct StyledLine {
+    pub spans: Vec<StyledSpan>
+}
+
+// Where the name ends if it is found at that point of the text as a word of its own. The text
+// is lowercased as the tokenizer does, so the name must be too
+fn found_at(text: &str, idx: usize, name: &str) -> Option<usize> {
+    let rest = &text[idx..];
+    let end = rest.char_indices()
+        .map(|(i, c)| (i + c.len_utf8(), tokenizer::fold_case(&rest[..i + c.len_utf8()])))
+        .take_while(|(_, folded)| folded.len() <= name.len())
+        .find(|(_, folded)| folded == name)
+        .map(|(end, _)| idx + end)?;
+    let before = text[..idx].chars().next_back().map(char::is_alphanumeric).unwrap_or(false);
+    let after = text[end..].chars().next().map(char::is_alphanumeric).unwrap_or(false);
+    if before || after { Option::None } else { Option::Some(end) }
+}
+
+impl StyledLine {
+
+    pub fn new() -> StyledLine {
+        StyledLine { spans: Vec::new() }
+    }
+
+    pub fn plain(text: &str) -> StyledLine {
+        StyledLine::new().push(Style::Plain, text)
+    }
+
+    pub fn styled(style: Style, text: &str) -> StyledLine {
+        StyledLine::new().push(style, text)
+    }
+
+    pub fn push(mut self, style: Style, text: &str) -> StyledLine {
+        if text.is_empty() {
+            return self;
+        }
+        match self.spans.last_mut() {
+            Option::Some(last) if last.style == style => last.text.push_str(text),
+            _ => self.spans.push(StyledSpan { style, text: text.to_string() })
+        }
+        self
+    }
+
+    // The line as plain text, for front-ends without styles
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    // Gives the style to every appearance of the names in the plain spans, the longest name
+    // first where several start at the same point
+    pub fn mark(self, names: &[String], style: Style) -> StyledLine {
+        let mut names: Vec<String> = names.iter().map(|n| tokenizer::fold_case(n)).filter(|n| !n.is_empty()).collect();
+        names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+        let mut marked = StyledLine::new();
+        for span in self.spans.into_iter() {
+            if span.style != Style::Plain {
+                marked = marked.push(span.style, &span.text);
+                continue;
+            }
+            let text = span.text.as_str();
+            let mut from = 0;
+            let mut idx = 0;
+            while idx < text.len() {
+                match names.iter().find_map(|n| found_at(text, idx, n)) {
+                    Option::Some(end) => {
+                        marked = marked.push(Style::Plain, &text[from..idx]).push(style, &text[idx..end]);
+                        idx = end;
+                        from = idx;
+                    },
+                    Option::None => idx += text[idx..].chars().next().map(char::len_utf8).unwrap_or(1)
+                }
+            }
+            marked = marked.push(Style::Plain, &text[from..]);
+        }
+        marked
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(text: &str, names: &[&str]) -> Vec<(Style, String)> {
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        StyledLine::plain(text).mark(&names, Style::Object).spans.into_iter().map(|s| (s.style, s.text)).collect()
+    }
+
+    #[test]
+    fn marks_names_whatever_their_case() {
+        assert_eq!(marked("A Brass Key lies here", &["brass key"]), vec!(
+            (Style::Plain, String::from("A ")),
+            (Style::Object, String::from("Brass Key")),
+            (Style::Plain, String::from(" lies here"))
+        ));
+        assert_eq!(marked("EL ÁRBOL", &["árbol"]), vec!(
+            (Style::Plain, String::from("EL ")),
+            (Style::Object, String::from("ÁRBOL"))
+        ));
+        assert_eq!(marked("Die STRASSE", &["Straße"]), vec!(
+            (Style::Plain, String::from("Die ")),
+            (Style::Object, String::from("STRASSE"))
+        ));
+    }
+
+    #[test]
+    fn marks_whole_words_the_longest_name_first() {
+        assert_eq!(marked("keys and a key ring", &["key", "key ring"]), vec!(
+            (Style::Plain, String::from("keys and a ")),
+            (Style::Object, String::from("key ring"))
+        ));
+        assert_eq!(marked("İstanbul key", &["key"]), vec!(
+            (Style::Plain, String::from("İstanbul ")),
+            (Style::Object, String::from("key"))
+        ));
+    }
+
+}
@@ -0,0 +1,196 @@
+// Letters with accents and the plain letter they stand for, once lowercased
+const ACCENTS: [(&str, char); 10] = [
+    ("àáâãäåā", 'a'),
+    ("çćč", 'c'),
+    ("èéêëēě", 'e'),
+    ("ìíîïī", 'i'),
+    ("ñńň", 'n'),
+    ("òóôõöøō", 'o'),
+    ("ùúûüūů", 'u'),
+    ("ýÿ", 'y'),
+    ("śš", 's'),
+    ("źżž", 'z')
+];
+
+// Where a token is in the line typed, in bytes
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize
+}
+
+impl Span {
+
+    // The span going from the start of this one to the end of the other
+    pub fn join(&self, other: &Span) -> Span {
+        Span { start: self.start.min(other.start), end: self.end.max(other.end) }
+    }
+
+    // What was typed in the span, as it was typed
+    pub fn source<'a>(&self, line: &'a str) -> &'a str {
+        line.get(self.start..self.end).unwrap_or_default()
+    }
+
+}
+
+// Marks opening and closing a literal text in a command
+const OPENING_QUOTES: [char; 3] = ['"', '“', '«'];
+const CLOSING_QUOTES: [char; 3] = ['"', '”', '»'];
+
+// A word of a command, normalised for matching, and where it came from. A literal is the text
+// between quotes, kept as it was typed
+#[derive(Clone, PartialEq, Debug)]
+pub struct Token {
+    pub text: String,
+    pub span: Span,
+    pub literal: bool
+}
+
+// Lowercases the text the same way whatever the language, so "STRASSE" and "Straße" match
+pub fn fold_case(text: &str) -> String {
+    text.to_lowercase().replace('ß', "ss").replace('ς', "σ")
+}
+
+pub fn fold_accents(text: &str) -> String {
+    text.chars()
+        .map(|c| ACCENTS.iter().find(|(accented, _)| accented.contains(c)).map(|(_, plain)| *plain).unwrap_or(c))
+        .collect()
+}
+
+// The text as the parser matches it: case folded, and without accents if they are ignored
+pub fn normalise(text: &str, ignore_accents: bool) -> String {
+    let folded = fold_case(text);
+    if ignore_accents { fold_accents(&folded) } else { folded }
+}
+
+// Apostrophes and hyphens belong to a word only between letters, as in "o'clock"
+fn joins(line: &str, idx: usize, c: char) -> bool {
+    let before = line[..idx].chars().next_back().map(char::is_alphanumeric).unwrap_or(false);
+    let after = line[idx + c.len_utf8()..].chars().next().map(char::is_alphanumeric).unwrap_or(false);
+    (c == '\'' || c == '’' || c == '-') && before && after
+}
+
+// Splits the line into words at any whitespace or punctuation. A question mark is a word of
+// its own, as it asks for help, and a text between quotes is a single literal token
+pub fn tokenize(line: &str, ignore_accents: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = Option::None;
+    let push = |tokens: &mut Vec<Token>, start: usize, end: usize| tokens.push(Token {
+        text: normalise(&line[start..end], ignore_accents),
+        span: Span { start, end },
+        literal: false
+    });
+    // Where the literal being read started, if any
+    let mut quote: Option<usize> = Option::None;
+    for (idx, c) in line.char_indices() {
+        if let Option::Some(from) = quote {
+            if CLOSING_QUOTES.contains(&c) {
+                tokens.push(literal(line, from, idx + c.len_utf8()));
+                quote = Option::None;
+            }
+            continue;
+        }
+        if OPENING_QUOTES.contains(&c) {
+            if let Option::Some(from) = start.take() {
+                push(&mut tokens, from, idx);
+            }
+            quote = Option::Some(idx);
+            continue;
+        }
+        let in_word = c.is_alphanumeric() || joins(line, idx, c);
+        match (in_word, start) {
+            (true, Option::None) => start = Option::Some(idx),
+            (false, Option::Some(from)) => {
+                push(&mut tokens, from, idx);
+                start = Option::None;
+            },
+            _ => {}
+        }
+        if c == '?' {
+            push(&mut tokens, idx, idx + 1);
+        }
+    }
+    if let Option::Some(from) = start {
+        push(&mut tokens, from, line.len());
+    }
+    // A quote left open runs to the end of the line
+    if let Option::Some(from) = quote {
+        tokens.push(literal(line, from, line.len()));
+    }
+    tokens
+}
+
+fn literal(line: &str, start: usize, end: usize) -> Token {
+    let text = &line[start..end];
+    let inner = text.strip_prefix(|c| OPENING_QUOTES.contains(&c)).unwrap_or(text);
+    let inner = inner.strip_suffix(|c| CLOSING_QUOTES.contains(&c)).unwrap_or(inner);
+    Token { text: inner.trim().to_string(), span: Span { start, end }, literal: true }
+}
+
+// The words of a text as the parser sees them, to compare what was said regardless of case,
+// spacing and punctuation
+pub fn words(text: &str, ignore_accents: bool) -> String {
+    tokenize(text, ignore_accents).iter().map(|t| t.text.clone()).collect::<Vec<String>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(line: &str, ignore_accents: bool) -> Vec<String> {
+        tokenize(line, ignore_accents).into_iter().map(|t| t.text).collect()
+    }
+
+    #[test]
+    fn folds_case_and_accents() {
+        assert_eq!(texts("Coge la LLAVE Dorada", false), vec!("coge", "la", "llave", "dorada"));
+        assert_eq!(texts("Abre el Baúl", true), vec!("abre", "el", "baul"));
+        assert_eq!(texts("Abre el Baúl", false), vec!("abre", "el", "baúl"));
+        assert_eq!(texts("ÑANDÚ", true), vec!("nandu"));
+        assert_eq!(texts("STRASSE Straße", false), vec!("strasse", "strasse"));
+    }
+
+    #[test]
+    fn keeps_the_spans_of_words_in_bytes() {
+        let line = "mira  el árbol";
+        let tokens = tokenize(line, true);
+        assert_eq!(tokens[2].text, "arbol");
+        assert_eq!(tokens[2].span, Span { start: 9, end: 15 });
+        assert_eq!(tokens[2].span.source(line), "árbol");
+    }
+
+    #[test]
+    fn splits_at_punctuation_but_not_inside_words() {
+        assert_eq!(texts("take key, then open door!", false), vec!("take", "key", "then", "open", "door"));
+        assert_eq!(texts("wind the o'clock jack-in-the-box", false), vec!("wind", "the", "o'clock", "jack-in-the-box"));
+        assert_eq!(texts("'quoted' - away", false), vec!("quoted", "away"));
+        assert_eq!(texts("what is this?", false), vec!("what", "is", "this", "?"));
+    }
+
+    #[test]
+    fn reads_quoted_text_as_a_literal() {
+        let tokens = tokenize("say \"Open Sesame\" now", false);
+        assert_eq!(tokens.len(), 3);
+        assert_eq!(tokens[1], Token { text: String::from("Open Sesame"), span: Span { start: 4, end: 17 }, literal: true });
+        assert!(!tokens[2].literal);
+        assert_eq!(texts("di «Ábrete, Sésamo»", true), vec!("di", "Ábrete, Sésamo"));
+        assert_eq!(texts("say “hello”", false), vec!("say", "hello"));
+    }
+
+    #[test]
+    fn an_open_quote_runs_to_the_end() {
+        let tokens = tokenize("say \"by iron and rust", false);
+        assert_eq!(tokens[1].text, "by iron and rust");
+        assert_eq!(tokens[1].span.end, 21);
+        assert!(tokens[1].literal);
+        assert_eq!(texts("say \"\"", false), vec!("say", ""));
+        assert_eq!(texts("say\"hi\"there", false), vec!("say", "hi", "there"));
+    }
+
+    #[test]
+    fn words_ignore_spacing_and_punctuation() {
+        assert_eq!(words("  By iron, and RUST! ", false), "by iron and rust");
+        assert_eq!(words("", false), "");
+    }
+
+}
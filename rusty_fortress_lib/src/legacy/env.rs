@@ -119,12 +119,6 @@ impl Environment {
         let keywords = self.get_all_keywords(&output);
         let objects = self.get_all_objects(&output);
         return match keywords.as_slice() {
-            [parser::Keyword::Quit] => {
-                (false, quit_message())
-            },
-            [parser::Keyword::Help] => {
-                (true, help_message())
-            },
             [parser::Keyword::Open] => {
                 (true, self.one_obj_fn(&objects, 
                     "What do you want to open?", 
@@ -1,3 +1,4 @@
+#[allow(dead_code, clippy::all)]
 mod legacy;
-mod universe;
+pub mod universe;
 pub mod app;
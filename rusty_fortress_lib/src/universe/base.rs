@@ -1,3 +1,11 @@
+use std::any::Any;
+use toml::value::Table;
+use crate::app::command::HandleCommand;
+use crate::universe::clock::Timer;
+use crate::universe::combat::Stats;
+use crate::universe::dialogue::Dialogue;
+use crate::universe::stack::Stack;
+
 // Object
 pub trait Name {
     fn name(&self) -> String;
@@ -26,4 +34,192 @@ pub trait Take {
 // Go throug
 pub trait GoThrough {
     fn go_through(&mut self) -> String;
-}
\ No newline at end of file
+}
+
+// Light
+pub trait Light {
+    fn light(&mut self) -> String;
+}
+
+// Extinguish
+pub trait Extinguish {
+    fn extinguish(&mut self) -> String;
+}
+
+// Talk to
+pub trait Talk {
+    fn talk(&mut self) -> String;
+}
+
+// Ask about
+pub trait AskAbout {
+    fn ask_about(&mut self, topic: &str) -> String;
+}
+
+// Give to
+pub trait GiveTo {
+    fn give_to(&mut self, item: &str) -> String;
+}
+
+// Location
+#[derive(Clone, PartialEq)]
+pub enum Location {
+    Nowhere,
+    Inventory,
+    Room(String),
+    HeldBy(String)
+}
+
+impl Location {
+
+    pub fn from_data(text: &str) -> Location {
+        match text {
+            "" | "nowhere" => Location::Nowhere,
+            "inventory" => Location::Inventory,
+            text if text.starts_with("held_by:") => Location::HeldBy(text["held_by:".len()..].to_string()),
+            room => Location::Room(room.to_string())
+        }
+    }
+
+    pub fn to_data(&self) -> String {
+        match self {
+            Location::Nowhere => String::from("nowhere"),
+            Location::Inventory => String::from("inventory"),
+            Location::Room(room) => room.to_string(),
+            Location::HeldBy(holder) => format!("held_by:{}", holder)
+        }
+    }
+
+}
+
+// Copies and compares things behind a Box, which Clone and PartialEq can't do on their own
+pub trait BoxedThing {
+    fn boxed(&self) -> Box<dyn Thing>;
+    fn as_any(&self) -> &dyn Any;
+    fn same(&self, other: &dyn Thing) -> bool;
+}
+
+impl<T: Thing + Clone + PartialEq + 'static> BoxedThing for T {
+
+    fn boxed(&self) -> Box<dyn Thing> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn same(&self, other: &dyn Thing) -> bool {
+        other.as_any().downcast_ref::<T>().map(|other| other == self).unwrap_or(false)
+    }
+
+}
+
+impl Clone for Box<dyn Thing> {
+    fn clone(&self) -> Box<dyn Thing> {
+        self.boxed()
+    }
+}
+
+impl PartialEq for Box<dyn Thing> {
+    fn eq(&self, other: &Box<dyn Thing>) -> bool {
+        self.same(other.as_ref())
+    }
+}
+
+// Thing
+pub trait Thing: Name + HandleCommand + BoxedThing {
+    fn id(&self) -> String;
+    fn location(&self) -> Location;
+    fn to_data(&self) -> Table;
+
+    fn is_at(&self, room: &str) -> bool {
+        self.location() == Location::Room(room.to_string())
+    }
+
+    fn move_to(&mut self, _location: Location) {}
+
+    // Moves what is in one place to another. Only stacks can be in several places at once
+    fn move_from(&mut self, from: &Location, to: Location) {
+        if self.location() == *from {
+            self.move_to(to);
+        }
+    }
+
+    fn accepts(&self, _item: &str) -> bool {
+        false
+    }
+
+    fn dialogue(&self) -> Option<&Dialogue> {
+        Option::None
+    }
+
+    // Damage range when used as a weapon
+    fn damage(&self) -> Option<(u32, u32)> {
+        Option::None
+    }
+
+    fn stats(&self) -> Option<&Stats> {
+        Option::None
+    }
+
+    fn stats_mut(&mut self) -> Option<&mut Stats> {
+        Option::None
+    }
+
+    // Counts of the stackable items, by place
+    fn stack(&self) -> Option<&Stack> {
+        Option::None
+    }
+
+    fn stack_mut(&mut self) -> Option<&mut Stack> {
+        Option::None
+    }
+
+    fn is_hostile(&self) -> bool {
+        false
+    }
+
+    fn provoke(&mut self) {}
+
+    fn exit_from(&self, _room: &str) -> Option<String> {
+        Option::None
+    }
+
+    // The room on the other side, open or not
+    fn leads_to(&self, _room: &str) -> Option<String> {
+        Option::None
+    }
+
+    // Sentence telling the thing is in the room, for the things with one of their own
+    fn here(&self) -> Option<String> {
+        Option::None
+    }
+
+    fn gives_light(&self) -> bool {
+        false
+    }
+
+    // Name of the current state (open, closed, lit...) for the things having one
+    fn state(&self) -> Option<String> {
+        Option::None
+    }
+
+    // Changes the state by name, returning false if the thing can't be in that state
+    fn set_state(&mut self, _state: &str) -> bool {
+        false
+    }
+
+    fn tick(&mut self) -> Option<String> {
+        Option::None
+    }
+
+    fn on_event(&mut self, _action: &str) -> Option<String> {
+        Option::None
+    }
+
+    // Timers the thing wants to register since it was last asked
+    fn timers(&mut self) -> Vec<Timer> {
+        Vec::new()
+    }
+}
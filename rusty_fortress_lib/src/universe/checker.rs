@@ -0,0 +1,263 @@
+use toml::Value;
+use toml::value::Table;
+use crate::universe::condition::Condition;
+use crate::universe::condition::Test;
+use crate::universe::data;
+use crate::universe::base::Location;
+use crate::universe::dialogue::Dialogue;
+use crate::universe::hint::Hint;
+use crate::universe::trigger::Action;
+use crate::universe::trigger::Trigger;
+use crate::universe::trigger::When;
+use crate::universe::vars::Vars;
+use crate::universe::world::World;
+
+const TAKEABLE: [&str; 4] = ["item", "light", "weapon", "stack"];
+
+// Rooms where a thing can be found
+fn places(thing: &Table) -> Vec<String> {
+    let mut places = data::get_str_list(thing, "route");
+    places.append(&mut data::get_str_list(thing, "rooms"));
+    places.push(data::get_str_or(thing, "location", ""));
+    places
+}
+
+// Everything the player can get to: rooms entered, items obtained and flags set
+struct Reach {
+    rooms: Vec<String>,
+    dark: Vec<String>,
+    items: Vec<String>,
+    flags: Vec<String>,
+    // Doors opened by triggers, which need no key
+    opened: Vec<String>
+}
+
+impl Reach {
+
+    fn size(&self) -> usize {
+        self.rooms.len() + self.items.len() + self.flags.len()
+    }
+
+    fn add(list: &mut Vec<String>, id: &str) {
+        if !list.iter().any(|i| i == id) {
+            list.push(id.to_string());
+        }
+    }
+
+    fn has_light(&self, things: &[Table]) -> bool {
+        things.iter().any(|t| data::get_str_or(t, "kind", "") == "light" && self.items.contains(&data::get_str_or(t, "id", "")))
+    }
+
+    // A room can only be searched when the player can see in it
+    fn sees(&self, room: &str, light: bool) -> bool {
+        self.rooms.iter().any(|r| r == room) && (light || !self.dark.iter().any(|r| r == room))
+    }
+
+    fn holds(&self, condition: &Condition) -> bool {
+        // Negated conditions, states of things and values of variables are assumed to be met
+        // at some point
+        condition.negated || match &condition.test {
+            Test::Flag(name) => self.flags.contains(name),
+            Test::Has(item) => self.items.contains(item),
+            Test::In(room) => self.rooms.contains(room),
+            Test::State(_, _) | Test::Var(_, _, _) => true
+        }
+    }
+
+    fn visit_trigger(&mut self, trigger: &Trigger, things: &[Table]) {
+        let light = self.has_light(things);
+        let thing = |id: &str| things.iter().find(|t| data::get_str_or(t, "id", "") == id);
+        let ready = match &trigger.when {
+            When::Enter(room) => self.rooms.contains(room),
+            When::Turn(_) | When::Say(_) => true,
+            When::Verb(_, id) => self.items.contains(id) ||
+                thing(id).map(|t| places(t).iter().any(|room| self.sees(room, light))).unwrap_or(false)
+        };
+        if !ready || !trigger.conditions.iter().all(|c| self.holds(c)) {
+            return;
+        }
+        for action in trigger.actions.iter() {
+            match action {
+                Action::Set(flag) | Action::Add(flag, _) => Reach::add(&mut self.flags, flag),
+                Action::Var(name, value) if value.is_set() => Reach::add(&mut self.flags, name),
+                Action::Move(id, Location::Inventory) => Reach::add(&mut self.items, id),
+                Action::Move(id, Location::Room(room)) if self.sees(room, light) => Reach::add(&mut self.items, id),
+                // Whatever a trigger opens can be gone through
+                Action::State(id, state) if state == "open" => {
+                    Reach::add(&mut self.opened, id);
+                    let rooms = thing(id).map(|t| data::get_str_list(t, "rooms")).unwrap_or_default();
+                    if rooms.iter().any(|room| self.sees(room, light)) {
+                        rooms.iter().for_each(|room| Reach::add(&mut self.rooms, room));
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn visit(&mut self, thing: &Table, things: &[Table]) -> Result<(), String> {
+        let light = self.has_light(things);
+        let kind = data::get_str(thing, "kind")?;
+        let id = data::get_str(thing, "id")?;
+        let location = data::get_str_or(thing, "location", "");
+        let seen = places(thing).iter().any(|room| self.sees(room, light));
+
+        match kind.as_str() {
+            "door" | "locked_door" => {
+                let unlocked = kind == "door" || self.items.contains(&data::get_str_or(thing, "key", ""));
+                let rooms = data::get_str_list(thing, "rooms");
+                if unlocked && rooms.iter().any(|room| self.sees(room, light)) {
+                    rooms.iter().for_each(|room| Reach::add(&mut self.rooms, room));
+                }
+            },
+            "npc" if seen => {
+                let dialogue = Dialogue::from_data(&data::get_tables(thing, "node"))?;
+                for node in dialogue.iter().flat_map(|d| d.nodes().iter()) {
+                    let choices: Vec<_> = node.choices.iter().filter(|c| c.conditions.iter().all(|cond| self.holds(cond))).collect();
+                    for choice in choices {
+                        choice.set.iter().for_each(|flag| Reach::add(&mut self.flags, flag));
+                        choice.give.iter().for_each(|item| Reach::add(&mut self.items, item));
+                    }
+                }
+            },
+            kind if TAKEABLE.contains(&kind) => {
+                let found = match location.strip_prefix("held_by:") {
+                    // Creatures drop what they carry when killed
+                    Option::Some(holder) => things.iter().any(|t| data::get_str_or(t, "id", "") == holder &&
+                        data::get_str_or(t, "kind", "") == "creature" && self.sees(&data::get_str_or(t, "location", ""), light)),
+                    Option::None => location == "inventory" || seen
+                };
+                if found {
+                    Reach::add(&mut self.items, &id);
+                }
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+}
+
+// Explores what can be done in a world, growing the reachable rooms, obtainable items and
+// set flags until nothing new turns up. Returns the problems found, none if the world is sound
+pub fn check(value: &Value) -> Result<Vec<String>, String> {
+    World::from_data(value)?;
+    let table = value.as_table().ok_or_else(|| String::from("A world must be a table"))?;
+    let rooms = data::get_tables(table, "room");
+    let things = data::get_tables(table, "thing");
+    let triggers = Trigger::list_from_data(table)?;
+    let player = table.get("player").and_then(|p| p.as_table()).cloned().unwrap_or_default();
+
+    let mut reach = Reach {
+        rooms: vec!(data::get_str(&player, "location")?),
+        dark: rooms.iter().filter(|r| data::get_bool_or(r, "dark", false)).map(|r| data::get_str_or(r, "id", "")).collect(),
+        items: Vec::new(),
+        flags: Vars::from_data(table).values().iter().filter(|(_, v)| v.is_set()).map(|(n, _)| n.clone()).collect(),
+        opened: Vec::new()
+    };
+    loop {
+        let size = reach.size();
+        for thing in things.iter() {
+            reach.visit(thing, &things)?;
+        }
+        for trigger in triggers.iter() {
+            reach.visit_trigger(trigger, &things);
+        }
+        if reach.size() == size {
+            break;
+        }
+    }
+
+    let mut problems = Vec::new();
+    for room in rooms.iter().map(|r| data::get_str_or(r, "id", "")) {
+        if !reach.rooms.contains(&room) {
+            problems.push(format!("Room '{}' can't be reached", room));
+        }
+    }
+    for thing in things.iter() {
+        let kind = data::get_str_or(thing, "kind", "");
+        let id = data::get_str_or(thing, "id", "");
+        if TAKEABLE.contains(&kind.as_str()) && !reach.items.contains(&id) {
+            problems.push(format!("Item '{}' can't be obtained", id));
+        }
+        let key = data::get_str_or(thing, "key", "");
+        if kind == "locked_door" && !reach.items.contains(&key) && !reach.opened.contains(&id) {
+            if key.is_empty() {
+                problems.push(format!("Locked door '{}' has no key and nothing opens it", id));
+            }
+            else {
+                problems.push(format!("Locked door '{}' has no reachable key '{}'", id, key));
+            }
+        }
+    }
+    for hint in Hint::list_from_data(table)?.iter().filter(|h| h.texts.is_empty()) {
+        problems.push(format!("Hint '{}' has no hints to give", hint.id));
+    }
+    Ok(problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A hall and a vault behind a locked door, with the key wherever it is put
+    fn vault(key_location: &str) -> Value {
+        format!(r#"
+            title = "Vault"
+
+            [player]
+            location = "hall"
+
+            [[room]]
+            id = "hall"
+            name = "Hall"
+            description = "A hall."
+
+            [[room]]
+            id = "vault"
+            name = "Vault"
+            description = "A vault."
+
+            [[thing]]
+            kind = "locked_door"
+            id = "vault_door"
+            name = "vault door"
+            description = "A heavy door."
+            rooms = ["hall", "vault"]
+            key = "key"
+
+            [[thing]]
+            kind = "item"
+            id = "key"
+            name = "key"
+            description = "A key."
+            location = "{}"
+        "#, key_location).parse::<Value>().unwrap()
+    }
+
+    #[test]
+    fn a_script_for_an_unknown_verb_is_turned_down() {
+        let mut world = vault("hall");
+        let mut script = Table::new();
+        script.insert(String::from("thing"), Value::String(String::from("key")));
+        script.insert(String::from("verb"), Value::String(String::from("yank")));
+        script.insert(String::from("code"), Value::String(String::from("say(\"No.\")")));
+        world.as_table_mut().unwrap().insert(String::from("script"), Value::Array(vec!(Value::Table(script))));
+        assert_eq!(check(&world).err(), Option::Some(String::from("Unknown verb 'yank' in the script for 'key'")));
+    }
+
+    #[test]
+    fn a_key_outside_its_door_is_sound() {
+        assert_eq!(check(&vault("hall")).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn a_key_behind_its_own_door_is_turned_down() {
+        assert_eq!(check(&vault("vault")).unwrap(), vec!(
+            String::from("Room 'vault' can't be reached"),
+            String::from("Locked door 'vault_door' has no reachable key 'key'"),
+            String::from("Item 'key' can't be obtained")
+        ));
+    }
+
+}
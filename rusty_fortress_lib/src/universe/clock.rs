@@ -0,0 +1,160 @@
+use std::convert::TryFrom;
+use toml::Value;
+use toml::value::Table;
+use crate::universe::data;
+
+// Request from a thing to be called back after some turns
+#[derive(Clone, PartialEq)]
+pub struct Timer {
+    pub turns: u32,
+    pub repeat: bool,
+    pub action: String
+}
+
+impl Timer {
+    pub fn after(turns: u32, action: &str) -> Timer {
+        Timer { turns, repeat: false, action: action.to_string() }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Event {
+    pub target: String,
+    pub action: String,
+    pub message: String,
+    due: u32,
+    every: Option<u32>
+}
+
+impl Event {
+
+    fn from_data(table: &Table, turn: u32) -> Result<Event, String> {
+        let every = turns(table, "every", 1)?;
+        let due = match (turns(table, "due", 0)?, turns(table, "after", 1)?, every) {
+            (Option::Some(due), _, _) => due,
+            (Option::None, Option::Some(after), _) => turn.saturating_add(after),
+            (Option::None, Option::None, Option::Some(every)) => turn.saturating_add(every),
+            _ => return Err(String::from("An event needs 'after' or 'every' turns"))
+        };
+        Ok(Event {
+            target: data::get_str_or(table, "target", ""),
+            action: data::get_str_or(table, "action", ""),
+            message: data::get_str_or(table, "message", ""),
+            due,
+            every
+        })
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = Table::new();
+        table.insert(String::from("target"), Value::String(self.target.clone()));
+        table.insert(String::from("action"), Value::String(self.action.clone()));
+        table.insert(String::from("message"), Value::String(self.message.clone()));
+        table.insert(String::from("due"), Value::Integer(self.due as i64));
+        if let Option::Some(every) = self.every {
+            table.insert(String::from("every"), Value::Integer(every as i64));
+        }
+        table
+    }
+
+}
+
+// Number of turns under a key, which must be between the least given and u32::MAX
+fn turns(table: &Table, key: &str, least: u32) -> Result<Option<u32>, String> {
+    match data::get_int(table, key) {
+        Option::Some(n) => match u32::try_from(n) {
+            Ok(n) if n >= least => Ok(Option::Some(n)),
+            _ => Err(format!("'{}' must be between {} and {}, not {}", key, least, u32::MAX, n))
+        },
+        Option::None => Ok(Option::None)
+    }
+}
+
+// Turn counter and scheduled events
+#[derive(Clone, PartialEq)]
+pub struct Clock {
+    turn: u32,
+    events: Vec<Event>
+}
+
+impl Clock {
+
+    pub fn new() -> Clock {
+        Clock {
+            turn: 0,
+            events: Vec::new()
+        }
+    }
+
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    pub fn schedule(&mut self, target: &str, timer: &Timer, message: &str) {
+        let turns = timer.turns.max(1);
+        self.events.push(Event {
+            target: target.to_string(),
+            action: timer.action.clone(),
+            message: message.to_string(),
+            due: self.turn.saturating_add(turns),
+            every: if timer.repeat { Option::Some(turns) } else { Option::None }
+        });
+    }
+
+    // Moves to the next turn and returns the events due on it
+    pub fn advance(&mut self) -> Vec<Event> {
+        self.turn = self.turn.saturating_add(1);
+        let turn = self.turn;
+        let due: Vec<Event> = self.events.iter().filter(|e| e.due <= turn).cloned().collect();
+        self.events.retain(|e| e.due > turn || e.every.is_some());
+        self.events.iter_mut()
+            .filter(|e| e.due <= turn)
+            .for_each(|e| e.due = turn.saturating_add(e.every.unwrap_or(1)));
+        due
+    }
+
+    pub fn from_data(table: &Table) -> Result<Clock, String> {
+        let turn = turns(table, "turn", 0)?.unwrap_or(0);
+        let mut events = Vec::new();
+        for event in data::get_tables(table, "event").iter() {
+            events.push(Event::from_data(event, turn)?);
+        }
+        Ok(Clock { turn, events })
+    }
+
+    pub fn to_data(&self, table: &mut Table) {
+        table.insert(String::from("turn"), Value::Integer(self.turn as i64));
+        table.insert(String::from("event"), Value::Array(self.events.iter().map(|e| Value::Table(e.to_data())).collect()));
+    }
+
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(key: &str, turns: i64) -> Table {
+        let mut table = Table::new();
+        table.insert(String::from(key), Value::Integer(turns));
+        table
+    }
+
+    #[test]
+    fn turns_out_of_range_are_rejected() {
+        assert!(Event::from_data(&event("due", -1), 0).is_err());
+        assert!(Event::from_data(&event("after", 0), 0).is_err());
+        assert!(Event::from_data(&event("every", u32::MAX as i64 + 1), 0).is_err());
+    }
+
+    #[test]
+    fn events_far_ahead_stay_at_the_last_turn() {
+        let event = Event::from_data(&event("after", u32::MAX as i64), 5).unwrap();
+        assert_eq!(event.due, u32::MAX);
+    }
+}
@@ -0,0 +1,103 @@
+use std::convert::TryFrom;
+use toml::Value;
+use toml::value::Table;
+use crate::universe::data;
+use crate::universe::rng::Rng;
+
+#[derive(Clone, PartialEq)]
+pub struct Stats {
+    pub hp: u32,
+    pub attack: u32,
+    pub defence: u32,
+    pub damage: (u32, u32)
+}
+
+impl Stats {
+
+    pub fn new(hp: u32, attack: u32, defence: u32, damage: (u32, u32)) -> Stats {
+        Stats { hp, attack, defence, damage }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hp > 0
+    }
+
+    pub fn from_data(table: &Table, default: &Stats) -> Result<Stats, String> {
+        let value = |key: &str, default: u32| match data::get_int(table, key) {
+            Option::Some(n) => stat(key, n),
+            Option::None => Ok(default)
+        };
+        Ok(Stats {
+            hp: value("hp", default.hp)?,
+            attack: value("attack", default.attack)?,
+            defence: value("defence", default.defence)?,
+            damage: damage_from_data(table)?.unwrap_or(default.damage)
+        })
+    }
+
+    pub fn to_data(&self, table: &mut Table) {
+        table.insert(String::from("hp"), Value::Integer(self.hp as i64));
+        table.insert(String::from("attack"), Value::Integer(self.attack as i64));
+        table.insert(String::from("defence"), Value::Integer(self.defence as i64));
+        table.insert(String::from("damage"), damage_to_data(self.damage));
+    }
+
+}
+
+// A stat read from a world file, which must fit in a u32
+pub fn stat(key: &str, n: i64) -> Result<u32, String> {
+    u32::try_from(n).map_err(|_| format!("'{}' must be between 0 and {}, not {}", key, u32::MAX, n))
+}
+
+pub fn damage_from_data(table: &Table) -> Result<Option<(u32, u32)>, String> {
+    let list = match table.get("damage").and_then(|d| d.as_array()) {
+        Option::Some(list) => list,
+        Option::None => return Ok(Option::None)
+    };
+    match list.as_slice() {
+        [min, max] => match (min.as_integer(), max.as_integer()) {
+            (Option::Some(min), Option::Some(max)) => Ok(Option::Some((stat("damage", min)?, stat("damage", max)?))),
+            _ => Ok(Option::None)
+        },
+        _ => Ok(Option::None)
+    }
+}
+
+pub fn damage_to_data(damage: (u32, u32)) -> Value {
+    Value::Array(vec!(Value::Integer(damage.0 as i64), Value::Integer(damage.1 as i64)))
+}
+
+// One blow: a d20 plus the attack must reach 10 plus the defence. Returns the damage dealt
+pub fn strike(rng: &mut Rng, attacker: &Stats, defender: &mut Stats) -> Option<u32> {
+    if rng.range(1, 20).saturating_add(attacker.attack) < defender.defence.saturating_add(10) {
+        return Option::None;
+    }
+    let damage = rng.range(attacker.damage.0, attacker.damage.1).max(1);
+    defender.hp = defender.hp.saturating_sub(damage);
+    Option::Some(damage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_out_of_range_are_rejected() {
+        let mut table = Table::new();
+        table.insert(String::from("attack"), Value::Integer(-1));
+        assert!(Stats::from_data(&table, &Stats::new(5, 0, 0, (1, 2))).is_err());
+        let mut table = Table::new();
+        table.insert(String::from("damage"), damage_to_data((1, 2)));
+        table.insert(String::from("hp"), Value::Integer(u32::MAX as i64 + 1));
+        assert!(Stats::from_data(&table, &Stats::new(5, 0, 0, (1, 2))).is_err());
+    }
+
+    #[test]
+    fn the_biggest_stats_never_overflow() {
+        let mut rng = Rng::new(1);
+        let attacker = Stats::new(1, u32::MAX, 0, (u32::MAX, u32::MAX));
+        let mut defender = Stats::new(5, 0, u32::MAX, (1, 2));
+        assert_eq!(strike(&mut rng, &attacker, &mut defender), Option::Some(u32::MAX));
+        assert!(!defender.is_alive());
+    }
+}
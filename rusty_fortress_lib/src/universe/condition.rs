@@ -0,0 +1,62 @@
+use crate::universe::vars::Compare;
+use crate::universe::vars::Var;
+
+#[derive(Clone, PartialEq)]
+pub enum Test {
+    Flag(String),
+    Has(String),
+    In(String),
+    State(String, String),
+    Var(String, Compare, Var)
+}
+
+// A test on the world state, written as "flag:name", "has:item", "in:room",
+// "state:thing:state" or "var:name<op>value" with one of = != < <= > >=, "!" negates it
+#[derive(Clone, PartialEq)]
+pub struct Condition {
+    pub negated: bool,
+    pub test: Test
+}
+
+impl Condition {
+
+    pub fn from_data(text: &str) -> Result<Condition, String> {
+        let negated = text.starts_with('!');
+        let body = text.trim_start_matches('!');
+        let test = match body.split_once(':') {
+            Option::Some(("flag", name)) => Test::Flag(name.to_string()),
+            Option::Some(("has", item)) => Test::Has(item.to_string()),
+            Option::Some(("in", room)) => Test::In(room.to_string()),
+            Option::Some(("state", rest)) => match rest.split_once(':') {
+                Option::Some((thing, state)) => Test::State(thing.to_string(), state.to_string()),
+                Option::None => return Err(format!("Unknown condition '{}'", text))
+            },
+            Option::Some(("var", rest)) => match Compare::split(rest) {
+                Option::Some((name, compare, value)) => Test::Var(name, compare, value),
+                Option::None => return Err(format!("Unknown condition '{}'", text))
+            },
+            _ => return Err(format!("Unknown condition '{}'", text))
+        };
+        Ok(Condition { negated, test })
+    }
+
+    pub fn to_data(&self) -> String {
+        let body = match &self.test {
+            Test::Flag(name) => format!("flag:{}", name),
+            Test::Has(item) => format!("has:{}", item),
+            Test::In(room) => format!("in:{}", room),
+            Test::State(thing, state) => format!("state:{}:{}", thing, state),
+            Test::Var(name, compare, value) => format!("var:{}{}{}", name, compare.symbol(), value)
+        };
+        if self.negated { format!("!{}", body) } else { body }
+    }
+
+    pub fn list_from_data(list: &[String]) -> Result<Vec<Condition>, String> {
+        list.iter().map(|text| Condition::from_data(text)).collect()
+    }
+
+    pub fn list_to_data(list: &[Condition]) -> Vec<String> {
+        list.iter().map(|c| c.to_data()).collect()
+    }
+
+}
@@ -0,0 +1,134 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::universe::base::Location;
+use crate::universe::base::Name;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::combat;
+use crate::universe::combat::Stats;
+use crate::universe::data;
+
+// Creature, friendly ones only fight back once attacked
+#[derive(Clone, PartialEq)]
+pub struct Creature {
+    id: String,
+    name: String,
+    description: String,
+    location: Location,
+    stats: Stats,
+    max_hp: u32,
+    hostile: bool
+}
+
+impl Name for Creature {
+    fn name(&self) -> std::string::String {
+        String::from(&self.name)
+    }
+}
+
+impl HandleCommand for Creature {
+    fn resolve_command(&mut self, command: &Command) -> String {
+        match command.keyword {
+            Option::Some(Keyword::View) => self.view(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
+        }
+    }
+}
+
+impl View for Creature {
+    fn view(&mut self) -> String {
+        if !self.stats.is_alive() {
+            message::named("creature.view_dead", &self.name)
+        }
+        else if self.stats.hp < self.max_hp {
+            message::text("creature.view_wounded", &Args::new().set("description", &self.description))
+        }
+        else {
+            String::from(&self.description)
+        }
+    }
+}
+
+impl Thing for Creature {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn move_to(&mut self, location: Location) {
+        self.location = location;
+    }
+
+    fn stats(&self) -> Option<&Stats> {
+        Option::Some(&self.stats)
+    }
+
+    fn stats_mut(&mut self) -> Option<&mut Stats> {
+        Option::Some(&mut self.stats)
+    }
+
+    fn is_hostile(&self) -> bool {
+        self.hostile && self.stats.is_alive()
+    }
+
+    fn provoke(&mut self) {
+        self.hostile = true;
+    }
+
+    fn here(&self) -> Option<String> {
+        if self.stats.is_alive() {
+            Option::None
+        }
+        else {
+            Option::Some(message::text("room.dead", &Args::new().set_name(&self.name)))
+        }
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = data::thing_table("creature", &self.id, &self.name, &self.description);
+        table.insert(String::from("location"), Value::String(self.location.to_data()));
+        self.stats.to_data(&mut table);
+        table.insert(String::from("max_hp"), Value::Integer(self.max_hp as i64));
+        table.insert(String::from("hostile"), Value::Boolean(self.hostile));
+        table
+    }
+
+}
+
+impl Creature {
+
+    pub fn new(id: &str, name: &str, location: Location, stats: Stats) -> Creature {
+        Creature {
+            id: String::from(id),
+            name: String::from(name),
+            description: String::from("It looks back at you."),
+            location,
+            max_hp: stats.hp,
+            stats,
+            hostile: true
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Result<Creature, String> {
+        let location = Location::from_data(&data::get_str_or(table, "location", ""));
+        let stats = Stats::from_data(table, &Stats::new(5, 0, 0, (1, 2)))?;
+        let mut creature = Creature::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?, location, stats);
+        creature.description = data::get_str_or(table, "description", &creature.description);
+        if let Option::Some(max_hp) = data::get_int(table, "max_hp") {
+            creature.max_hp = combat::stat("max_hp", max_hp)?;
+        }
+        creature.hostile = data::get_bool_or(table, "hostile", true);
+        Ok(creature)
+    }
+
+}
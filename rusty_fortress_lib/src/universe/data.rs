@@ -0,0 +1,48 @@
+use toml::Value;
+use toml::value::Table;
+
+pub fn get_str(table: &Table, key: &str) -> Result<String, String> {
+    match table.get(key).and_then(|v| v.as_str()) {
+        Option::Some(text) => Ok(text.to_string()),
+        Option::None => Err(format!("Missing text field '{}'", key))
+    }
+}
+
+pub fn get_str_or(table: &Table, key: &str, default: &str) -> String {
+    get_str(table, key).unwrap_or_else(|_| default.to_string())
+}
+
+pub fn get_bool_or(table: &Table, key: &str, default: bool) -> bool {
+    table.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
+}
+
+pub fn get_int(table: &Table, key: &str) -> Option<i64> {
+    table.get(key).and_then(|v| v.as_integer())
+}
+
+pub fn get_str_list(table: &Table, key: &str) -> Vec<String> {
+    match table.get(key).and_then(|v| v.as_array()) {
+        Option::Some(list) => list.iter().filter_map(|v| v.as_str()).map(|s| s.to_string()).collect(),
+        Option::None => Vec::new()
+    }
+}
+
+pub fn get_tables(table: &Table, key: &str) -> Vec<Table> {
+    match table.get(key).and_then(|v| v.as_array()) {
+        Option::Some(list) => list.iter().filter_map(|v| v.as_table()).cloned().collect(),
+        Option::None => Vec::new()
+    }
+}
+
+pub fn str_list(list: &[String]) -> Value {
+    Value::Array(list.iter().map(|s| Value::String(s.to_string())).collect())
+}
+
+pub fn thing_table(kind: &str, id: &str, name: &str, description: &str) -> Table {
+    let mut table = Table::new();
+    table.insert(String::from("kind"), Value::String(kind.to_string()));
+    table.insert(String::from("id"), Value::String(id.to_string()));
+    table.insert(String::from("name"), Value::String(name.to_string()));
+    table.insert(String::from("description"), Value::String(description.to_string()));
+    table
+}
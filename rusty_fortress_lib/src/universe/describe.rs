@@ -0,0 +1,46 @@
+use crate::app::message;
+use crate::app::message::Args;
+use crate::universe::base::Location;
+use crate::universe::base::Thing;
+use crate::universe::room::Room;
+
+// Sentence telling the thing is in the room: its own when it has one, else one made up
+// from where it leads, what it is and its state
+fn sentence(thing: &dyn Thing, room: &Room, rooms: &[Room]) -> String {
+    let leads_to = thing.leads_to(&room.id).and_then(|id| rooms.iter().find(|r| r.id == id));
+    if let Option::Some(other) = leads_to {
+        let state = thing.state().map(|s| message::state(&s)).unwrap_or_default();
+        let args = Args::new().set_name(&thing.name()).set("room", &other.name).set("state", &state);
+        return message::text("room.door", &args);
+    }
+    if let Option::Some(text) = thing.here() {
+        return text;
+    }
+    if let Option::Some(stack) = thing.stack() {
+        let count = stack.count(&Location::Room(room.id.clone()));
+        let id = if count == 1 { "room.item" } else { "room.stack" };
+        return message::text(id, &Args::new().set_name(&thing.name()).set("counted", &stack.counted(&thing.name(), count)));
+    }
+    if thing.stats().is_some() || thing.dialogue().is_some() {
+        return message::text("room.creature", &Args::new().set_name(&thing.name()));
+    }
+    let name = match thing.state() {
+        Option::Some(state) => message::text("thing.with_state", &Args::new().set_name(&thing.name()).set("state", &message::state(&state))),
+        Option::None => thing.name()
+    };
+    message::text("room.item", &Args::new().set_name(&name))
+}
+
+// What the player sees in a room: its name, its description unless brief, and a sentence
+// for each thing in it
+pub fn room(room: &Room, things: &[&dyn Thing], rooms: &[Room], brief: bool) -> String {
+    let mut lines = vec!(room.name.clone());
+    if !brief && !room.description.is_empty() {
+        lines.push(room.description.clone());
+    }
+    let sentences: Vec<String> = things.iter().map(|t| sentence(*t, room, rooms)).collect();
+    if !sentences.is_empty() {
+        lines.push(sentences.join(" "));
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,100 @@
+use toml::Value;
+use toml::value::Table;
+use crate::universe::condition::Condition;
+use crate::universe::data;
+
+#[derive(Clone, PartialEq)]
+pub struct Choice {
+    pub text: String,
+    pub reply: String,
+    pub conditions: Vec<Condition>,
+    pub set: Vec<String>,
+    pub clear: Vec<String>,
+    pub give: Vec<String>,
+    pub goto: Option<String>
+}
+
+impl Choice {
+
+    fn from_data(table: &Table) -> Result<Choice, String> {
+        Ok(Choice {
+            text: data::get_str(table, "text")?,
+            reply: data::get_str_or(table, "reply", ""),
+            conditions: Condition::list_from_data(&data::get_str_list(table, "if"))?,
+            set: data::get_str_list(table, "set"),
+            clear: data::get_str_list(table, "clear"),
+            give: data::get_str_list(table, "give"),
+            goto: data::get_str(table, "goto").ok()
+        })
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = Table::new();
+        table.insert(String::from("text"), Value::String(self.text.clone()));
+        table.insert(String::from("reply"), Value::String(self.reply.clone()));
+        table.insert(String::from("if"), data::str_list(&Condition::list_to_data(&self.conditions)));
+        table.insert(String::from("set"), data::str_list(&self.set));
+        table.insert(String::from("clear"), data::str_list(&self.clear));
+        table.insert(String::from("give"), data::str_list(&self.give));
+        if let Option::Some(goto) = &self.goto {
+            table.insert(String::from("goto"), Value::String(goto.clone()));
+        }
+        table
+    }
+
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Node {
+    pub id: String,
+    pub text: String,
+    pub choices: Vec<Choice>
+}
+
+// Conversation tree, the first node is where every conversation starts
+#[derive(Clone, PartialEq)]
+pub struct Dialogue {
+    nodes: Vec<Node>
+}
+
+impl Dialogue {
+
+    pub fn from_data(list: &[Table]) -> Result<Option<Dialogue>, String> {
+        let mut nodes = Vec::new();
+        for table in list.iter() {
+            let mut choices = Vec::new();
+            for choice in data::get_tables(table, "choice").iter() {
+                choices.push(Choice::from_data(choice)?);
+            }
+            nodes.push(Node {
+                id: data::get_str(table, "id")?,
+                text: data::get_str_or(table, "text", ""),
+                choices
+            });
+        }
+        Ok(if nodes.is_empty() { Option::None } else { Option::Some(Dialogue { nodes }) })
+    }
+
+    pub fn to_data(&self) -> Value {
+        Value::Array(self.nodes.iter().map(|node| {
+            let mut table = Table::new();
+            table.insert(String::from("id"), Value::String(node.id.clone()));
+            table.insert(String::from("text"), Value::String(node.text.clone()));
+            table.insert(String::from("choice"), Value::Array(node.choices.iter().map(|c| Value::Table(c.to_data())).collect()));
+            Value::Table(table)
+        }).collect())
+    }
+
+    pub fn start(&self) -> &Node {
+        &self.nodes[0]
+    }
+
+    pub fn node(&self, id: &str) -> Option<&Node> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+}
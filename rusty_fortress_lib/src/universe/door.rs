@@ -1,134 +1,374 @@
+use toml::Value;
+use toml::value::Table;
 use crate::app::command::DefaultCommandHandler;
 use crate::app::command::Command;
 use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
 use crate::app::parser::Keyword;
+use crate::universe::base::GoThrough;
+use crate::universe::base::Location;
 use crate::universe::base::Open;
 use crate::universe::base::OpenWith;
 use crate::universe::base::Name;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::clock::Timer;
+use crate::universe::data;
 
 #[derive(Clone, PartialEq)]
 enum DoorState {
-    OPENED,
-    CLOSED
+    Opened,
+    Closed
+}
+
+fn door_rooms(table: &Table) -> Result<(String, String), String> {
+    let rooms = data::get_str_list(table, "rooms");
+    match rooms.as_slice() {
+        [from, to] => Ok((from.to_string(), to.to_string())),
+        _ => Err(String::from("A door must connect exactly two rooms"))
+    }
+}
+
+fn door_state(table: &Table) -> DoorState {
+    if data::get_bool_or(table, "open", false) { DoorState::Opened } else { DoorState::Closed }
+}
+
+fn door_data(kind: &str, id: &str, name: &str, description: &str, rooms: &(String, String), state: &DoorState) -> Table {
+    let mut table = data::thing_table(kind, id, name, description);
+    table.insert(String::from("rooms"), data::str_list(&[rooms.0.clone(), rooms.1.clone()]));
+    table.insert(String::from("open"), Value::Boolean(*state == DoorState::Opened));
+    table
+}
+
+fn door_exit(rooms: &(String, String), state: &DoorState, room: &str) -> Option<String> {
+    if *state == DoorState::Closed { Option::None } else { door_leads_to(rooms, room) }
+}
+
+fn door_leads_to(rooms: &(String, String), room: &str) -> Option<String> {
+    if rooms.0 == room {
+        Option::Some(rooms.1.clone())
+    }
+    else if rooms.1 == room {
+        Option::Some(rooms.0.clone())
+    }
+    else {
+        Option::None
+    }
+}
+
+fn door_state_name(state: &DoorState) -> String {
+    match state {
+        DoorState::Opened => String::from("open"),
+        DoorState::Closed => String::from("closed")
+    }
+}
+
+fn door_state_from_name(name: &str) -> Option<DoorState> {
+    match name {
+        "open" => Option::Some(DoorState::Opened),
+        "closed" => Option::Some(DoorState::Closed),
+        _ => Option::None
+    }
+}
+
+fn door_view(description: &str, state: &DoorState) -> String {
+    let args = Args::new().set("description", description);
+    match state {
+        DoorState::Opened => message::text("door.view_open", &args),
+        DoorState::Closed => message::text("door.view_closed", &args)
+    }
+}
+
+fn door_go_through(name: &str, state: &DoorState) -> String {
+    let args = Args::new().set_name(name);
+    match state {
+        DoorState::Opened => message::text("go.through", &args),
+        DoorState::Closed => message::text("door.closed", &args)
+    }
 }
 
 // Door
 #[derive(Clone, PartialEq)]
 pub struct Door {
+    id: String,
     name: String,
-    state: DoorState
+    description: String,
+    rooms: (String, String),
+    state: DoorState,
+    closes_after: Option<u32>,
+    timers: Vec<Timer>
 }
 
 impl Name for Door {
-    fn name(&self) -> std::string::String { 
+    fn name(&self) -> std::string::String {
         String::from(&self.name)
     }
 }
 
-impl HandleCommand for Door {   
-    fn resolve_command(&mut self, command: &Command) -> String { 
+impl HandleCommand for Door {
+    fn resolve_command(&mut self, command: &Command) -> String {
         match command.keyword {
-            Option::Some(Keyword::Open) => {
-                self.open()
-            },
-            _ => {
-                let mut handler = DefaultCommandHandler::new();
-                let text = handler.resolve_command(&command);
-                text
-            }
+            Option::Some(Keyword::Open) => self.open(),
+            Option::Some(Keyword::View) => self.view(),
+            Option::Some(Keyword::GoThrough) => self.go_through(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
         }
     }
 }
 
 impl Open for Door {
-    fn open(&mut self) -> String { 
-        return if self.state == DoorState::CLOSED {
-            self.state = DoorState::OPENED;
-            String::from("You open the door")
+    fn open(&mut self) -> String {
+        if self.state == DoorState::Closed {
+            self.state = DoorState::Opened;
+            if let Option::Some(turns) = self.closes_after {
+                self.timers.push(Timer::after(turns, "close"));
+            }
+            message::named("door.open", &self.name)
         }
         else {
-            String::from("The door is already opened")   
+            message::named("door.already_open", &self.name)
         }
     }
 }
 
+impl View for Door {
+    fn view(&mut self) -> String {
+        door_view(&self.description, &self.state)
+    }
+}
+
+impl GoThrough for Door {
+    fn go_through(&mut self) -> String {
+        door_go_through(&self.name, &self.state)
+    }
+}
+
+impl Thing for Door {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        Location::Room(self.rooms.0.clone())
+    }
+
+    fn is_at(&self, room: &str) -> bool {
+        self.rooms.0 == room || self.rooms.1 == room
+    }
+
+    fn exit_from(&self, room: &str) -> Option<String> {
+        door_exit(&self.rooms, &self.state, room)
+    }
+
+    fn leads_to(&self, room: &str) -> Option<String> {
+        door_leads_to(&self.rooms, room)
+    }
+
+    fn state(&self) -> Option<String> {
+        Option::Some(door_state_name(&self.state))
+    }
+
+    fn set_state(&mut self, state: &str) -> bool {
+        match door_state_from_name(state) {
+            Option::Some(state) => {
+                self.state = state;
+                true
+            },
+            Option::None => false
+        }
+    }
+
+    fn on_event(&mut self, action: &str) -> Option<String> {
+        if action == "close" && self.state == DoorState::Opened {
+            self.state = DoorState::Closed;
+            Option::Some(message::named("door.slams", &self.name))
+        }
+        else {
+            Option::None
+        }
+    }
+
+    fn timers(&mut self) -> Vec<Timer> {
+        self.timers.drain(..).collect()
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = door_data("door", &self.id, &self.name, &self.description, &self.rooms, &self.state);
+        if let Option::Some(turns) = self.closes_after {
+            table.insert(String::from("closes_after"), Value::Integer(turns as i64));
+        }
+        table
+    }
+
+}
+
 impl Door {
-    pub fn new(name: &str) -> Door {
+
+    pub fn new(id: &str, name: &str, rooms: (String, String)) -> Door {
         Door {
+            id: String::from(id),
             name: String::from(name),
-            state: DoorState::CLOSED
+            description: String::from("It's just a door."),
+            rooms,
+            state: DoorState::Closed,
+            closes_after: Option::None,
+            timers: Vec::new()
         }
     }
+
+    pub fn from_data(table: &Table) -> Result<Door, String> {
+        let mut door = Door::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?, door_rooms(table)?);
+        door.description = data::get_str_or(table, "description", &door.description);
+        door.state = door_state(table);
+        door.closes_after = data::get_int(table, "closes_after").map(|n| n.max(1) as u32);
+        Ok(door)
+    }
+
 }
 
 // Locked door
 #[derive(Clone, PartialEq)]
 pub struct LockedDoor {
+    id: String,
     name: String,
+    description: String,
+    rooms: (String, String),
     state: DoorState,
     key: String
 }
 
 impl Name for LockedDoor {
-    fn name(&self) -> std::string::String { 
+    fn name(&self) -> std::string::String {
         String::from(&self.name)
     }
 }
 
-impl HandleCommand for LockedDoor {   
-    fn resolve_command(&mut self, command: &Command) -> String { 
+impl HandleCommand for LockedDoor {
+    fn resolve_command(&mut self, command: &Command) -> String {
         match command.keyword {
             Option::Some(Keyword::Open) => {
                 self.open()
             },
             Option::Some(Keyword::OpenWith) => {
                 let default_key = String::from("");
-                let key = command.objects.get(0).unwrap_or(&default_key);
+                let key = command.objects.first().unwrap_or(&default_key);
                 self.open_with(key)
             },
-            _ => {
-                let mut handler = DefaultCommandHandler::new();
-                let text = handler.resolve_command(&command);
-                text
-            }
+            Option::Some(Keyword::View) => self.view(),
+            Option::Some(Keyword::GoThrough) => self.go_through(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
         }
     }
 }
 
 impl OpenWith for LockedDoor {
-    fn open_with(&mut self, key: &str) -> String { 
-        return if self.state == DoorState::CLOSED {
-            if self.key == key {
-                self.state = DoorState::OPENED;
-                String::from("You open the door")
+    fn open_with(&mut self, key: &str) -> String {
+        if self.state == DoorState::Closed {
+            // A door without a key only opens by other means, such as a password
+            if !self.key.is_empty() && self.key == key {
+                self.state = DoorState::Opened;
+                message::named("door.open", &self.name)
             }
             else {
-                String::from("The door is locked. Try another key")
+                message::named("door.wrong_key", &self.name)
             }
         }
         else {
-            String::from("The door is already opened")   
+            message::named("door.already_open", &self.name)
         }
     }
 }
 
 impl Open for LockedDoor {
-    fn open(&mut self) -> String { 
-        return if self.state == DoorState::CLOSED {
-            String::from("The door is locked")
+    fn open(&mut self) -> String {
+        if self.state == DoorState::Closed {
+            message::named("door.locked", &self.name)
         }
         else {
-            String::from("The door is already opened")   
+            message::named("door.already_open", &self.name)
+        }
+    }
+}
+
+impl View for LockedDoor {
+    fn view(&mut self) -> String {
+        door_view(&self.description, &self.state)
+    }
+}
+
+impl GoThrough for LockedDoor {
+    fn go_through(&mut self) -> String {
+        door_go_through(&self.name, &self.state)
+    }
+}
+
+impl Thing for LockedDoor {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        Location::Room(self.rooms.0.clone())
+    }
+
+    fn is_at(&self, room: &str) -> bool {
+        self.rooms.0 == room || self.rooms.1 == room
+    }
+
+    fn exit_from(&self, room: &str) -> Option<String> {
+        door_exit(&self.rooms, &self.state, room)
+    }
+
+    fn leads_to(&self, room: &str) -> Option<String> {
+        door_leads_to(&self.rooms, room)
+    }
+
+    fn state(&self) -> Option<String> {
+        Option::Some(door_state_name(&self.state))
+    }
+
+    fn set_state(&mut self, state: &str) -> bool {
+        match door_state_from_name(state) {
+            Option::Some(state) => {
+                self.state = state;
+                true
+            },
+            Option::None => false
+        }
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = door_data("locked_door", &self.id, &self.name, &self.description, &self.rooms, &self.state);
+        if !self.key.is_empty() {
+            table.insert(String::from("key"), Value::String(self.key.clone()));
         }
+        table
     }
+
 }
 
 impl LockedDoor {
-    pub fn new(name: &str, key: &str) -> LockedDoor {
+
+    pub fn new(id: &str, name: &str, rooms: (String, String), key: &str) -> LockedDoor {
         LockedDoor {
+            id: String::from(id),
             name: String::from(name),
-            state: DoorState::CLOSED,
+            description: String::from("It's a sturdy door with a keyhole."),
+            rooms,
+            state: DoorState::Closed,
             key: String::from(key)
         }
     }
-}
\ No newline at end of file
+
+    pub fn from_data(table: &Table) -> Result<LockedDoor, String> {
+        let mut door = LockedDoor::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?,
+            door_rooms(table)?, &data::get_str_or(table, "key", ""));
+        door.description = data::get_str_or(table, "description", &door.description);
+        door.state = door_state(table);
+        Ok(door)
+    }
+
+}
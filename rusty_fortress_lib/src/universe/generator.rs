@@ -0,0 +1,145 @@
+use toml::Value;
+use toml::value::Table;
+use crate::universe::combat;
+use crate::universe::data;
+use crate::universe::rng::Rng;
+
+const ROOM_NAMES: [&str; 16] = [
+    "Guard Room", "Armory", "Crypt", "Chapel", "Kitchen", "Library", "Barracks", "Dungeon",
+    "Well Room", "Storeroom", "Gallery", "Throne Room", "Forge", "Vault", "Kennels", "Larder"
+];
+
+const ROOM_MOODS: [&str; 6] = [
+    "Water drips from the ceiling.",
+    "The air smells of mould.",
+    "Cobwebs cover every corner.",
+    "Your steps echo on the cold stones.",
+    "Something scratches behind the walls.",
+    "A cold draught makes you shiver."
+];
+
+const METALS: [&str; 8] = ["iron", "bronze", "silver", "golden", "copper", "bone", "crystal", "black"];
+
+const TREASURES: [(&str, &str); 4] = [
+    ("chalice", "gold chalice"),
+    ("crown", "jewelled crown"),
+    ("ring", "ruby ring"),
+    ("idol", "jade idol")
+];
+
+// Name, hit points, attack, defence and damage range
+type Monster = (&'static str, u32, u32, u32, (u32, u32));
+
+const MONSTERS: [Monster; 4] = [
+    ("goblin", 6, 1, 0, (1, 3)),
+    ("skeleton", 8, 2, 1, (1, 4)),
+    ("giant spider", 5, 2, 0, (1, 3)),
+    ("orc", 10, 2, 1, (2, 5))
+];
+
+fn room_id(idx: usize) -> String {
+    format!("room{}", idx)
+}
+
+fn placed(kind: &str, id: &str, name: &str, description: &str, location: &str) -> Table {
+    let mut table = data::thing_table(kind, id, name, description);
+    table.insert(String::from("location"), Value::String(location.to_string()));
+    table
+}
+
+fn shuffle<T>(rng: &mut Rng, list: &mut [T]) {
+    for i in (1..list.len()).rev() {
+        let j = rng.range(0, i as u32) as usize;
+        list.swap(i, j);
+    }
+}
+
+// Builds a world as a tree of rooms grown from the start room. Every room hangs from an earlier
+// one, so the key of a locked door is always left in an earlier room, reachable before the door
+pub fn generate(seed: u64) -> Value {
+    let mut rng = Rng::new(seed);
+    let mut names = ROOM_NAMES.to_vec();
+    shuffle(&mut rng, &mut names);
+    let mut metals = METALS.to_vec();
+    shuffle(&mut rng, &mut metals);
+    let count = rng.range(5, 9) as usize;
+
+    let mut rooms = Vec::new();
+    for (idx, name) in names.iter().take(count).enumerate() {
+        let mut room = Table::new();
+        let mood = ROOM_MOODS[rng.range(0, ROOM_MOODS.len() as u32 - 1) as usize];
+        room.insert(String::from("id"), Value::String(room_id(idx)));
+        room.insert(String::from("name"), Value::String(name.to_string()));
+        room.insert(String::from("description"), Value::String(format!("You are in the {}. {}", name.to_lowercase(), mood)));
+        room.insert(String::from("dark"), Value::Boolean(idx > 0 && rng.range(1, 4) == 1));
+        rooms.push(room);
+    }
+
+    let mut things = Vec::new();
+    things.push(placed("light", "lamp", "lamp", "A sturdy lamp that never runs dry.", &room_id(0)));
+    let mut sword = placed("weapon", "sword", "short sword", "A short but sharp sword.", &room_id(0));
+    sword.insert(String::from("damage"), combat::damage_to_data((2, 6)));
+    things.push(sword);
+
+    for (idx, name) in names.iter().enumerate().take(count).skip(1) {
+        let parent = rng.range(0, idx as u32 - 1) as usize;
+        let name = name.to_lowercase();
+        let mut door = data::thing_table("door", &format!("door{}", idx), &format!("{} door", name),
+            &format!("A door leading to the {}.", name));
+        door.insert(String::from("rooms"), data::str_list(&[room_id(parent), room_id(idx)]));
+        if !metals.is_empty() && rng.range(1, 3) == 1 {
+            let metal = metals.pop().unwrap();
+            let key = format!("key{}", idx);
+            door.insert(String::from("kind"), Value::String(String::from("locked_door")));
+            door.insert(String::from("key"), Value::String(key.clone()));
+            let location = room_id(rng.range(0, idx as u32 - 1) as usize);
+            things.push(placed("item", &key, &format!("{} key", metal), &format!("A small {} key.", metal), &location));
+        }
+        things.push(door);
+
+        if rng.range(1, 3) == 1 {
+            let (monster, hp, attack, defence, damage) = MONSTERS[rng.range(0, MONSTERS.len() as u32 - 1) as usize];
+            let mut creature = placed("creature", &format!("monster{}", idx), monster,
+                &format!("A nasty {} guarding the {}.", monster, name), &room_id(idx));
+            combat::Stats::new(hp, attack, defence, damage).to_data(&mut creature);
+            things.push(creature);
+        }
+    }
+
+    let mut treasures = TREASURES.to_vec();
+    shuffle(&mut rng, &mut treasures);
+    for (id, name) in treasures.iter().take(2) {
+        let location = room_id(rng.range(1, count as u32 - 1) as usize);
+        things.push(placed("item", id, name, &format!("A {}, worth a fortune.", name), &location));
+    }
+
+    let mut player = Table::new();
+    player.insert(String::from("location"), Value::String(room_id(0)));
+
+    let mut world = Table::new();
+    world.insert(String::from("title"), Value::String(format!("Generated dungeon #{}", seed)));
+    world.insert(String::from("player"), Value::Table(player));
+    world.insert(String::from("room"), Value::Array(rooms.into_iter().map(Value::Table).collect()));
+    world.insert(String::from("thing"), Value::Array(things.into_iter().map(Value::Table).collect()));
+    Value::Table(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::universe::checker;
+
+    #[test]
+    fn generated_worlds_pass_the_checker() {
+        for seed in 0..200 {
+            assert_eq!(checker::check(&generate(seed)), Ok(Vec::new()), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn a_seed_always_gives_the_same_world() {
+        assert_eq!(generate(7), generate(7));
+        assert_ne!(generate(7), generate(8));
+    }
+
+}
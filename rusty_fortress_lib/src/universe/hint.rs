@@ -0,0 +1,51 @@
+use toml::Value;
+use toml::value::Table;
+use crate::universe::condition::Condition;
+use crate::universe::data;
+
+// Hints for a puzzle, one more each time the player asks while its conditions hold, so the
+// first ones nudge and the last one gives the answer away
+#[derive(Clone, PartialEq)]
+pub struct Hint {
+    pub id: String,
+    pub conditions: Vec<Condition>,
+    pub texts: Vec<String>,
+    shown: usize
+}
+
+impl Hint {
+
+    // The next hint, and its number. Once all have been given the last one is repeated
+    pub fn reveal(&mut self) -> Option<(usize, &str)> {
+        if self.texts.is_empty() {
+            return Option::None;
+        }
+        self.shown = (self.shown + 1).min(self.texts.len());
+        Option::Some((self.shown, &self.texts[self.shown - 1]))
+    }
+
+    pub fn list_from_data(table: &Table) -> Result<Vec<Hint>, String> {
+        let mut hints = Vec::new();
+        for hint in data::get_tables(table, "hint").iter() {
+            hints.push(Hint {
+                id: data::get_str(hint, "id")?,
+                conditions: Condition::list_from_data(&data::get_str_list(hint, "if"))?,
+                texts: data::get_str_list(hint, "hints"),
+                shown: data::get_int(hint, "shown").unwrap_or(0).max(0) as usize
+            });
+        }
+        Ok(hints)
+    }
+
+    pub fn list_to_data(hints: &[Hint]) -> Value {
+        Value::Array(hints.iter().map(|hint| {
+            let mut table = Table::new();
+            table.insert(String::from("id"), Value::String(hint.id.clone()));
+            table.insert(String::from("if"), data::str_list(&Condition::list_to_data(&hint.conditions)));
+            table.insert(String::from("hints"), data::str_list(&hint.texts));
+            table.insert(String::from("shown"), Value::Integer(hint.shown as i64));
+            Value::Table(table)
+        }).collect())
+    }
+
+}
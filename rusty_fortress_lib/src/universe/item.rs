@@ -0,0 +1,109 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::universe::base::Location;
+use crate::universe::base::Name;
+use crate::universe::base::Take;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::data;
+
+// Item
+#[derive(Clone, PartialEq)]
+pub struct Item {
+    id: String,
+    name: String,
+    description: String,
+    here: String,
+    location: Location
+}
+
+impl Name for Item {
+    fn name(&self) -> std::string::String {
+        String::from(&self.name)
+    }
+}
+
+impl HandleCommand for Item {
+    fn resolve_command(&mut self, command: &Command) -> String {
+        match command.keyword {
+            Option::Some(Keyword::Take) => self.take(),
+            Option::Some(Keyword::View) => self.view(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
+        }
+    }
+}
+
+impl Take for Item {
+    fn take(&mut self) -> String {
+        if self.location == Location::Inventory {
+            message::text("take.already", &Args::new())
+        }
+        else {
+            self.location = Location::Inventory;
+            message::named("take.done", &self.name)
+        }
+    }
+}
+
+impl View for Item {
+    fn view(&mut self) -> String {
+        String::from(&self.description)
+    }
+}
+
+impl Thing for Item {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn move_to(&mut self, location: Location) {
+        self.location = location;
+    }
+
+    fn here(&self) -> Option<String> {
+        if self.here.is_empty() { Option::None } else { Option::Some(self.here.clone()) }
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = data::thing_table("item", &self.id, &self.name, &self.description);
+        table.insert(String::from("location"), Value::String(self.location.to_data()));
+        if !self.here.is_empty() {
+            table.insert(String::from("here"), Value::String(self.here.clone()));
+        }
+        table
+    }
+
+}
+
+impl Item {
+
+    pub fn new(id: &str, name: &str, location: Location) -> Item {
+        Item {
+            id: String::from(id),
+            name: String::from(name),
+            description: String::from("Nothing special about it."),
+            here: String::new(),
+            location
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Result<Item, String> {
+        let location = Location::from_data(&data::get_str_or(table, "location", ""));
+        let mut item = Item::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?, location);
+        item.description = data::get_str_or(table, "description", &item.description);
+        item.here = data::get_str_or(table, "here", "");
+        Ok(item)
+    }
+
+}
@@ -0,0 +1,183 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::universe::base::Extinguish;
+use crate::universe::base::Light;
+use crate::universe::base::Location;
+use crate::universe::base::Name;
+use crate::universe::base::Take;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::data;
+
+// Light source (torch, lamp...). Without fuel it burns forever
+#[derive(Clone, PartialEq)]
+pub struct LightSource {
+    id: String,
+    name: String,
+    description: String,
+    location: Location,
+    lit: bool,
+    fuel: Option<u32>
+}
+
+impl Name for LightSource {
+    fn name(&self) -> std::string::String {
+        String::from(&self.name)
+    }
+}
+
+impl HandleCommand for LightSource {
+    fn resolve_command(&mut self, command: &Command) -> String {
+        match command.keyword {
+            Option::Some(Keyword::Take) => self.take(),
+            Option::Some(Keyword::View) => self.view(),
+            Option::Some(Keyword::Light) => self.light(),
+            Option::Some(Keyword::Extinguish) => self.extinguish(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
+        }
+    }
+}
+
+impl Take for LightSource {
+    fn take(&mut self) -> String {
+        if self.location == Location::Inventory {
+            message::text("take.already", &Args::new())
+        }
+        else {
+            self.location = Location::Inventory;
+            message::named("take.done", &self.name)
+        }
+    }
+}
+
+impl View for LightSource {
+    fn view(&mut self) -> String {
+        if self.lit {
+            message::text("light.view_lit", &Args::new().set("description", &self.description))
+        }
+        else if self.fuel == Option::Some(0) {
+            message::text("light.view_burnt_out", &Args::new().set("description", &self.description))
+        }
+        else {
+            message::text("light.view_unlit", &Args::new().set("description", &self.description))
+        }
+    }
+}
+
+impl Light for LightSource {
+    fn light(&mut self) -> String {
+        if self.lit {
+            message::named("light.already", &self.name)
+        }
+        else if self.fuel == Option::Some(0) {
+            message::named("light.burnt_out", &self.name)
+        }
+        else {
+            self.lit = true;
+            message::named("light.done", &self.name)
+        }
+    }
+}
+
+impl Extinguish for LightSource {
+    fn extinguish(&mut self) -> String {
+        if self.lit {
+            self.lit = false;
+            message::named("extinguish.done", &self.name)
+        }
+        else {
+            message::named("extinguish.not_lit", &self.name)
+        }
+    }
+}
+
+impl Thing for LightSource {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn move_to(&mut self, location: Location) {
+        self.location = location;
+    }
+
+    fn gives_light(&self) -> bool {
+        self.lit
+    }
+
+    fn state(&self) -> Option<String> {
+        Option::Some(String::from(if self.lit { "lit" } else { "unlit" }))
+    }
+
+    fn set_state(&mut self, state: &str) -> bool {
+        match state {
+            "lit" if self.fuel != Option::Some(0) => self.lit = true,
+            "unlit" => self.lit = false,
+            _ => return false
+        }
+        true
+    }
+
+    fn tick(&mut self) -> Option<String> {
+        if !self.lit {
+            return Option::None;
+        }
+        match self.fuel {
+            Option::Some(1) => {
+                self.fuel = Option::Some(0);
+                self.lit = false;
+                Option::Some(message::named("light.burns_out", &self.name))
+            },
+            Option::Some(fuel) => {
+                self.fuel = Option::Some(fuel - 1);
+                if fuel == 4 { Option::Some(message::named("light.flickers", &self.name)) } else { Option::None }
+            },
+            Option::None => Option::None
+        }
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = data::thing_table("light", &self.id, &self.name, &self.description);
+        table.insert(String::from("location"), Value::String(self.location.to_data()));
+        table.insert(String::from("lit"), Value::Boolean(self.lit));
+        if let Option::Some(fuel) = self.fuel {
+            table.insert(String::from("fuel"), Value::Integer(fuel as i64));
+        }
+        table
+    }
+
+}
+
+impl LightSource {
+
+    pub fn new(id: &str, name: &str, location: Location, fuel: Option<u32>) -> LightSource {
+        LightSource {
+            id: String::from(id),
+            name: String::from(name),
+            description: String::from("It gives some light."),
+            location,
+            lit: false,
+            fuel
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Result<LightSource, String> {
+        let location = Location::from_data(&data::get_str_or(table, "location", ""));
+        let fuel = data::get_int(table, "fuel").map(|f| f.max(0) as u32);
+        let mut light = LightSource::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?, location, fuel);
+        light.description = data::get_str_or(table, "description", &light.description);
+        light.lit = data::get_bool_or(table, "lit", false) && fuel != Option::Some(0);
+        Ok(light)
+    }
+
+}
@@ -1,2 +1,24 @@
 pub mod base;
-pub mod door;
\ No newline at end of file
+pub mod checker;
+pub mod clock;
+pub mod combat;
+pub mod condition;
+pub mod creature;
+pub mod data;
+pub mod describe;
+pub mod dialogue;
+pub mod door;
+pub mod generator;
+pub mod hint;
+pub mod item;
+pub mod light;
+pub mod npc;
+pub mod rng;
+pub mod room;
+pub mod score;
+pub mod script;
+pub mod stack;
+pub mod trigger;
+pub mod vars;
+pub mod weapon;
+pub mod world;
\ No newline at end of file
@@ -0,0 +1,208 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::app::tokenizer;
+use crate::universe::base::AskAbout;
+use crate::universe::base::GiveTo;
+use crate::universe::base::Location;
+use crate::universe::base::Name;
+use crate::universe::base::Talk;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::data;
+use crate::universe::dialogue::Dialogue;
+
+fn answers_from_data(table: &Table, key: &str) -> Vec<(String, String)> {
+    match table.get(key).and_then(|v| v.as_table()) {
+        Option::Some(answers) => answers.iter()
+            .filter_map(|(k, v)| v.as_str().map(|text| (k.to_lowercase(), text.to_string())))
+            .collect(),
+        Option::None => Vec::new()
+    }
+}
+
+fn answers_to_data(answers: &[(String, String)]) -> Value {
+    let mut table = Table::new();
+    answers.iter().for_each(|(k, v)| { table.insert(k.clone(), Value::String(v.clone())); });
+    Value::Table(table)
+}
+
+// Non player character
+#[derive(Clone, PartialEq)]
+pub struct Npc {
+    id: String,
+    name: String,
+    description: String,
+    location: Location,
+    greeting: String,
+    default_answer: String,
+    refusal: String,
+    topics: Vec<(String, String)>,
+    wants: Vec<(String, String)>,
+    route: Vec<String>,
+    route_every: u32,
+    route_wait: u32,
+    route_pos: usize,
+    dialogue: Option<Dialogue>
+}
+
+impl Name for Npc {
+    fn name(&self) -> std::string::String {
+        String::from(&self.name)
+    }
+}
+
+impl HandleCommand for Npc {
+    fn resolve_command(&mut self, command: &Command) -> String {
+        let default_arg = String::from("");
+        let arg = command.objects.first().unwrap_or(&default_arg);
+        match command.keyword {
+            Option::Some(Keyword::View) => self.view(),
+            Option::Some(Keyword::Talk) => self.talk(),
+            Option::Some(Keyword::AskAbout) => self.ask_about(arg),
+            Option::Some(Keyword::GiveTo) => self.give_to(arg),
+            _ => DefaultCommandHandler::new().resolve_command(command)
+        }
+    }
+}
+
+fn says(name: &str, text: &str) -> String {
+    message::text("npc.says", &Args::new().set_name(name).set("text", text))
+}
+
+impl View for Npc {
+    fn view(&mut self) -> String {
+        String::from(&self.description)
+    }
+}
+
+impl Talk for Npc {
+    fn talk(&mut self) -> String {
+        says(&self.name, &self.greeting)
+    }
+}
+
+impl AskAbout for Npc {
+    fn ask_about(&mut self, topic: &str) -> String {
+        let topic = tokenizer::normalise(topic, message::ignore_accents());
+        let answer = self.topics.iter()
+            .map(|(t, answer)| (tokenizer::normalise(t, message::ignore_accents()), answer))
+            .find(|(t, _)| *t == topic || topic.ends_with(&format!(" {}", t)))
+            .map(|(_, answer)| answer.clone())
+            .unwrap_or_else(|| self.default_answer.clone());
+        says(&self.name, &answer)
+    }
+}
+
+impl GiveTo for Npc {
+    fn give_to(&mut self, item: &str) -> String {
+        match self.wants.iter().find(|(i, _)| i == item) {
+            Option::Some((_, thanks)) => says(&self.name, thanks),
+            Option::None => says(&self.name, &self.refusal)
+        }
+    }
+}
+
+impl Thing for Npc {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn move_to(&mut self, location: Location) {
+        self.location = location;
+    }
+
+    fn dialogue(&self) -> Option<&Dialogue> {
+        self.dialogue.as_ref()
+    }
+
+    fn accepts(&self, item: &str) -> bool {
+        self.wants.iter().any(|(i, _)| i == item)
+    }
+
+    // Walks one step of the route every few turns
+    fn tick(&mut self) -> Option<String> {
+        if self.route.is_empty() {
+            return Option::None;
+        }
+        self.route_wait += 1;
+        if self.route_wait >= self.route_every {
+            self.route_wait = 0;
+            self.route_pos = (self.route_pos + 1) % self.route.len();
+            self.location = Location::Room(self.route[self.route_pos].clone());
+        }
+        Option::None
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = data::thing_table("npc", &self.id, &self.name, &self.description);
+        table.insert(String::from("location"), Value::String(self.location.to_data()));
+        table.insert(String::from("greeting"), Value::String(self.greeting.clone()));
+        table.insert(String::from("default_answer"), Value::String(self.default_answer.clone()));
+        table.insert(String::from("refusal"), Value::String(self.refusal.clone()));
+        table.insert(String::from("topics"), answers_to_data(&self.topics));
+        table.insert(String::from("wants"), answers_to_data(&self.wants));
+        table.insert(String::from("route"), data::str_list(&self.route));
+        table.insert(String::from("route_every"), Value::Integer(self.route_every as i64));
+        table.insert(String::from("route_wait"), Value::Integer(self.route_wait as i64));
+        table.insert(String::from("route_pos"), Value::Integer(self.route_pos as i64));
+        if let Option::Some(dialogue) = &self.dialogue {
+            table.insert(String::from("node"), dialogue.to_data());
+        }
+        table
+    }
+
+}
+
+impl Npc {
+
+    pub fn new(id: &str, name: &str, location: Location) -> Npc {
+        Npc {
+            id: String::from(id),
+            name: String::from(name),
+            description: String::from("Just somebody."),
+            location,
+            greeting: String::from("Hello."),
+            default_answer: String::from("I don't know anything about that."),
+            refusal: String::from("I don't want that."),
+            topics: Vec::new(),
+            wants: Vec::new(),
+            route: Vec::new(),
+            route_every: 1,
+            route_wait: 0,
+            route_pos: 0,
+            dialogue: Option::None
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Result<Npc, String> {
+        let location = Location::from_data(&data::get_str_or(table, "location", ""));
+        let mut npc = Npc::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?, location);
+        npc.description = data::get_str_or(table, "description", &npc.description);
+        npc.greeting = data::get_str_or(table, "greeting", &npc.greeting);
+        npc.default_answer = data::get_str_or(table, "default_answer", &npc.default_answer);
+        npc.refusal = data::get_str_or(table, "refusal", &npc.refusal);
+        npc.topics = answers_from_data(table, "topics");
+        npc.wants = answers_from_data(table, "wants");
+        npc.route = data::get_str_list(table, "route");
+        npc.route_every = data::get_int(table, "route_every").unwrap_or(1).max(1) as u32;
+        npc.route_wait = data::get_int(table, "route_wait").unwrap_or(0).max(0) as u32;
+        npc.route_pos = data::get_int(table, "route_pos").unwrap_or(0).max(0) as usize;
+        if npc.route_pos >= npc.route.len() {
+            npc.route_pos = 0;
+        }
+        npc.dialogue = Dialogue::from_data(&data::get_tables(table, "node"))?;
+        Ok(npc)
+    }
+
+}
@@ -0,0 +1,70 @@
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+// Small xorshift generator, good enough for dice rolls
+#[derive(Clone, PartialEq)]
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: if seed == 0 { 0x2545_f491_4f6c_dd1d } else { seed } }
+    }
+
+    pub fn time_seed() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+    }
+
+    pub fn from_time() -> Rng {
+        Rng::new(Rng::time_seed())
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    // Number between min and max, both included. The span is counted in u64, as the whole
+    // u32 range holds one number more than u32 does
+    pub fn range(&mut self, min: u32, max: u32) -> u32 {
+        if max <= min {
+            return min;
+        }
+        min + (self.next() % ((max - min) as u64 + 1)) as u32
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_stays_within_bounds() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            let n = rng.range(3, 8);
+            assert!((3..=8).contains(&n));
+        }
+        assert_eq!(rng.range(5, 5), 5);
+        assert_eq!(rng.range(9, 2), 9);
+    }
+
+    #[test]
+    fn range_takes_the_whole_u32_range() {
+        let mut rng = Rng::new(1);
+        for _ in 0..1000 {
+            rng.range(0, u32::MAX);
+            assert!(rng.range(u32::MAX - 1, u32::MAX) >= u32::MAX - 1);
+        }
+    }
+
+}
@@ -0,0 +1,46 @@
+use toml::Value;
+use toml::value::Table;
+use crate::universe::data;
+
+// Room
+#[derive(Clone, PartialEq)]
+pub struct Room {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub dark: bool,
+    pub visited: bool
+}
+
+impl Room {
+
+    pub fn new(id: &str, name: &str) -> Room {
+        Room {
+            id: String::from(id),
+            name: String::from(name),
+            description: String::from(""),
+            dark: false,
+            visited: false
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Result<Room, String> {
+        let id = data::get_str(table, "id")?;
+        let mut room = Room::new(&id, &data::get_str_or(table, "name", &id));
+        room.description = data::get_str_or(table, "description", "");
+        room.dark = data::get_bool_or(table, "dark", false);
+        room.visited = data::get_bool_or(table, "visited", false);
+        Ok(room)
+    }
+
+    pub fn to_data(&self) -> Table {
+        let mut table = Table::new();
+        table.insert(String::from("id"), Value::String(self.id.clone()));
+        table.insert(String::from("name"), Value::String(self.name.clone()));
+        table.insert(String::from("description"), Value::String(self.description.clone()));
+        table.insert(String::from("dark"), Value::Boolean(self.dark));
+        table.insert(String::from("visited"), Value::Boolean(self.visited));
+        table
+    }
+
+}
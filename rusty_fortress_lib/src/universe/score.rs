@@ -0,0 +1,123 @@
+use toml::Value;
+use toml::value::Table;
+use crate::universe::condition::Condition;
+use crate::universe::data;
+
+// Points given the first time all the conditions hold
+#[derive(Clone, PartialEq)]
+pub struct Milestone {
+    pub id: String,
+    pub conditions: Vec<Condition>,
+    pub points: u32,
+    pub message: String
+}
+
+impl Milestone {
+
+    fn from_data(table: &Table) -> Result<Milestone, String> {
+        Ok(Milestone {
+            id: data::get_str(table, "id")?,
+            conditions: Condition::list_from_data(&data::get_str_list(table, "if"))?,
+            points: data::get_int(table, "points").unwrap_or(0).max(0) as u32,
+            message: data::get_str_or(table, "message", "")
+        })
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = Table::new();
+        table.insert(String::from("id"), Value::String(self.id.clone()));
+        table.insert(String::from("if"), data::str_list(&Condition::list_to_data(&self.conditions)));
+        table.insert(String::from("points"), Value::Integer(self.points as i64));
+        table.insert(String::from("message"), Value::String(self.message.clone()));
+        table
+    }
+
+}
+
+// The game is over as soon as all the conditions hold
+#[derive(Clone, PartialEq)]
+pub struct Ending {
+    pub conditions: Vec<Condition>,
+    pub message: String
+}
+
+impl Ending {
+
+    fn list_from_data(table: &Table, key: &str) -> Result<Vec<Ending>, String> {
+        let mut endings = Vec::new();
+        for ending in data::get_tables(table, key).iter() {
+            endings.push(Ending {
+                conditions: Condition::list_from_data(&data::get_str_list(ending, "if"))?,
+                message: data::get_str_or(ending, "message", "")
+            });
+        }
+        Ok(endings)
+    }
+
+    fn list_to_data(endings: &[Ending]) -> Value {
+        Value::Array(endings.iter().map(|ending| {
+            let mut table = Table::new();
+            table.insert(String::from("if"), data::str_list(&Condition::list_to_data(&ending.conditions)));
+            table.insert(String::from("message"), Value::String(ending.message.clone()));
+            Value::Table(table)
+        }).collect())
+    }
+
+}
+
+#[derive(Clone, PartialEq)]
+pub enum Outcome {
+    Victory(String),
+    Defeat(String)
+}
+
+// Milestones, the ones already scored, and the ways the game can end
+#[derive(Clone, PartialEq)]
+pub struct Score {
+    pub milestones: Vec<Milestone>,
+    scored: Vec<String>,
+    pub victories: Vec<Ending>,
+    pub defeats: Vec<Ending>
+}
+
+impl Score {
+
+    pub fn is_scored(&self, id: &str) -> bool {
+        self.scored.iter().any(|s| s == id)
+    }
+
+    pub fn achieve(&mut self, id: &str) {
+        if !self.is_scored(id) {
+            self.scored.push(id.to_string());
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.milestones.iter().filter(|m| self.is_scored(&m.id)).map(|m| m.points).sum()
+    }
+
+    pub fn max_points(&self) -> u32 {
+        self.milestones.iter().map(|m| m.points).sum()
+    }
+
+    pub fn from_data(table: &Table) -> Result<Score, String> {
+        let mut milestones = Vec::new();
+        for milestone in data::get_tables(table, "milestone").iter() {
+            milestones.push(Milestone::from_data(milestone)?);
+        }
+        Ok(Score {
+            milestones,
+            scored: data::get_str_list(table, "scored"),
+            victories: Ending::list_from_data(table, "victory")?,
+            defeats: Ending::list_from_data(table, "defeat")?
+        })
+    }
+
+    pub fn to_data(&self, table: &mut Table) {
+        table.insert(String::from("milestone"), Value::Array(self.milestones.iter().map(|m| Value::Table(m.to_data())).collect()));
+        table.insert(String::from("scored"), data::str_list(&self.scored));
+        table.insert(String::from("victory"), Ending::list_to_data(&self.victories));
+        table.insert(String::from("defeat"), Ending::list_to_data(&self.defeats));
+    }
+
+}
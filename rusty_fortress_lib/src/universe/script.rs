@@ -0,0 +1,654 @@
+use std::fmt;
+use toml::Value;
+use toml::value::Table;
+use crate::universe::data;
+use crate::universe::trigger::VERBS;
+
+// Most statements a script may run, so a runaway loop can't hang the game
+const MAX_STEPS: u32 = 10000;
+// Deepest nesting of blocks and expressions a script may have. Running a script recurses as
+// deep as it is nested, so this keeps both parsing and running it from overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Val {
+    Int(i64),
+    Str(String),
+    Bool(bool)
+}
+
+impl Val {
+
+    pub fn is_true(&self) -> bool {
+        match self {
+            Val::Int(n) => *n != 0,
+            Val::Str(s) => !s.is_empty(),
+            Val::Bool(b) => *b
+        }
+    }
+
+    pub fn as_int(&self) -> Result<i64, String> {
+        match self {
+            Val::Int(n) => Ok(*n),
+            Val::Bool(b) => Ok(*b as i64),
+            Val::Str(s) => s.trim().parse::<i64>().map_err(|_| format!("'{}' is not a number", s))
+        }
+    }
+
+}
+
+impl fmt::Display for Val {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Val::Int(n) => write!(f, "{}", n),
+            Val::Str(s) => write!(f, "{}", s),
+            Val::Bool(b) => write!(f, "{}", b)
+        }
+    }
+}
+
+// What a script can do to the world, and nothing else
+pub trait Host {
+    fn call(&mut self, name: &str, args: &[Val]) -> Result<Val, String>;
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Str(String),
+    Name(String),
+    Sym(&'static str)
+}
+
+const SYMBOLS: [&str; 17] = ["==", "!=", "<=", ">=", "<", ">", "=", "+", "-", "*", "/", "%", "(", ")", "{", "}", ","];
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        }
+        else if c.is_whitespace() {
+            i += 1;
+        }
+        else if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        }
+        else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            let n = digits.parse::<i64>().map_err(|_| format!("line {}: number too big", line))?;
+            tokens.push((Token::Int(n), line));
+        }
+        else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    Option::None => return Err(format!("line {}: unterminated text", line)),
+                    Option::Some('"') => break,
+                    Option::Some('\\') => {
+                        match chars.get(i + 1) {
+                            Option::Some('n') => s.push('\n'),
+                            Option::Some(other) => s.push(*other),
+                            Option::None => return Err(format!("line {}: unterminated text", line))
+                        }
+                        i += 1;
+                    },
+                    Option::Some(other) => {
+                        if *other == '\n' {
+                            line += 1;
+                        }
+                        s.push(*other);
+                    }
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(s), line));
+        }
+        else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Name(chars[start..i].iter().collect()), line));
+        }
+        else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+                Option::Some(sym) => {
+                    tokens.push((Token::Sym(sym), line));
+                    i += sym.len();
+                },
+                Option::None => return Err(format!("line {}: unexpected '{}'", line, c))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone)]
+enum Expr {
+    Value(Val),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>)
+}
+
+#[derive(Clone)]
+enum Stmt {
+    Let(String, Expr),
+    Assign(String, Expr),
+    If(Vec<(Expr, Vec<Stmt>)>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    Return,
+    Expr(Expr)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    depth: usize
+}
+
+impl Parser {
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or_else(|| self.tokens.last()).map(|(_, line)| *line).unwrap_or(1)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        Err(format!("line {}: {}", self.line(), message))
+    }
+
+    // Goes one level deeper, each nested block or expression and each operator chained to
+    // another counting as one
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH { self.error("too deeply nested") } else { Ok(()) }
+    }
+
+    fn leave(&mut self, levels: usize) {
+        self.depth -= levels;
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn is_sym(&self, sym: &str) -> bool {
+        matches!(self.peek(), Option::Some(Token::Sym(s)) if *s == sym)
+    }
+
+    fn is_name(&self, name: &str) -> bool {
+        matches!(self.peek(), Option::Some(Token::Name(n)) if n == name)
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<(), String> {
+        if self.is_sym(sym) {
+            self.pos += 1;
+            Ok(())
+        }
+        else {
+            self.error(&format!("expected '{}'", sym))
+        }
+    }
+
+    fn name(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Option::Some(Token::Name(name)) => {
+                let name = name.clone();
+                self.pos += 1;
+                Ok(name)
+            },
+            _ => self.error("expected a name")
+        }
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect_sym("{")?;
+        self.enter()?;
+        let mut stmts = Vec::new();
+        while !self.is_sym("}") {
+            if self.peek().is_none() {
+                return self.error("expected '}'");
+            }
+            stmts.push(self.statement()?);
+        }
+        self.pos += 1;
+        self.leave(1);
+        Ok(stmts)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        if self.is_name("let") {
+            self.pos += 1;
+            let name = self.name()?;
+            self.expect_sym("=")?;
+            return Ok(Stmt::Let(name, self.expression()?));
+        }
+        if self.is_name("if") {
+            let mut branches = Vec::new();
+            let mut otherwise = Vec::new();
+            self.pos += 1;
+            branches.push((self.expression()?, self.block()?));
+            while self.is_name("else") {
+                self.pos += 1;
+                if self.is_name("if") {
+                    self.pos += 1;
+                    branches.push((self.expression()?, self.block()?));
+                }
+                else {
+                    otherwise = self.block()?;
+                    break;
+                }
+            }
+            return Ok(Stmt::If(branches, otherwise));
+        }
+        if self.is_name("while") {
+            self.pos += 1;
+            return Ok(Stmt::While(self.expression()?, self.block()?));
+        }
+        if self.is_name("return") {
+            self.pos += 1;
+            return Ok(Stmt::Return);
+        }
+        if let (Option::Some((Token::Name(name), _)), Option::Some((Token::Sym("="), _))) = (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)) {
+            let name = name.clone();
+            self.pos += 2;
+            return Ok(Stmt::Assign(name, self.expression()?));
+        }
+        Ok(Stmt::Expr(self.expression()?))
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        self.enter()?;
+        let mut levels = 1;
+        let mut left = self.and_expression()?;
+        while self.is_name("or") {
+            self.pos += 1;
+            self.enter()?;
+            levels += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.and_expression()?));
+        }
+        self.leave(levels);
+        Ok(left)
+    }
+
+    fn and_expression(&mut self) -> Result<Expr, String> {
+        let mut levels = 0;
+        let mut left = self.not_expression()?;
+        while self.is_name("and") {
+            self.pos += 1;
+            self.enter()?;
+            levels += 1;
+            left = Expr::And(Box::new(left), Box::new(self.not_expression()?));
+        }
+        self.leave(levels);
+        Ok(left)
+    }
+
+    fn not_expression(&mut self) -> Result<Expr, String> {
+        if self.is_name("not") {
+            self.pos += 1;
+            self.enter()?;
+            let expr = Expr::Not(Box::new(self.not_expression()?));
+            self.leave(1);
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn binary(&mut self, ops: &[&'static str], next: fn(&mut Parser) -> Result<Expr, String>) -> Result<Expr, String> {
+        let mut levels = 0;
+        let mut left = next(self)?;
+        while let Option::Some(op) = ops.iter().find(|op| self.is_sym(op)) {
+            self.pos += 1;
+            self.enter()?;
+            levels += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(next(self)?));
+        }
+        self.leave(levels);
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        self.binary(&["==", "!=", "<=", ">=", "<", ">"], Parser::sum)
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        self.binary(&["+", "-"], Parser::product)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        self.binary(&["*", "/", "%"], Parser::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.is_sym("-") {
+            self.pos += 1;
+            self.enter()?;
+            let expr = Expr::Neg(Box::new(self.unary()?));
+            self.leave(1);
+            return Ok(expr);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let token = match self.peek() {
+            Option::Some(token) => token.clone(),
+            Option::None => return self.error("unexpected end of script")
+        };
+        self.pos += 1;
+        match token {
+            Token::Int(n) => Ok(Expr::Value(Val::Int(n))),
+            Token::Str(s) => Ok(Expr::Value(Val::Str(s))),
+            Token::Name(name) if name == "true" => Ok(Expr::Value(Val::Bool(true))),
+            Token::Name(name) if name == "false" => Ok(Expr::Value(Val::Bool(false))),
+            Token::Name(name) if self.is_sym("(") => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                while !self.is_sym(")") {
+                    args.push(self.expression()?);
+                    if !self.is_sym(")") {
+                        self.expect_sym(",")?;
+                    }
+                }
+                self.pos += 1;
+                Ok(Expr::Call(name, args))
+            },
+            Token::Name(name) => Ok(Expr::Var(name)),
+            Token::Sym("(") => {
+                let expr = self.expression()?;
+                self.expect_sym(")")?;
+                Ok(expr)
+            },
+            Token::Sym(sym) => {
+                self.pos -= 1;
+                self.error(&format!("unexpected '{}'", sym))
+            }
+        }
+    }
+
+}
+
+enum Flow {
+    Next,
+    Return
+}
+
+struct Run<'a> {
+    host: &'a mut dyn Host,
+    vars: Vec<(String, Val)>,
+    steps: u32
+}
+
+impl<'a> Run<'a> {
+
+    fn step(&mut self) -> Result<(), String> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            Err(String::from("the script ran for too long"))
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    fn var(&self, name: &str) -> Result<Val, String> {
+        self.vars.iter().rev().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+            .ok_or_else(|| format!("unknown variable '{}'", name))
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> Result<Flow, String> {
+        for stmt in stmts.iter() {
+            if let Flow::Return = self.statement(stmt)? {
+                return Ok(Flow::Return);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<Flow, String> {
+        self.step()?;
+        match stmt {
+            Stmt::Let(name, expr) => {
+                let value = self.eval(expr)?;
+                self.vars.push((name.clone(), value));
+            },
+            Stmt::Assign(name, expr) => {
+                let value = self.eval(expr)?;
+                match self.vars.iter_mut().rev().find(|(n, _)| n == name) {
+                    Option::Some(var) => var.1 = value,
+                    Option::None => return Err(format!("unknown variable '{}'", name))
+                }
+            },
+            Stmt::If(branches, otherwise) => {
+                for (condition, body) in branches.iter() {
+                    if self.eval(condition)?.is_true() {
+                        return self.block(body);
+                    }
+                }
+                return self.block(otherwise);
+            },
+            Stmt::While(condition, body) => {
+                while self.eval(condition)?.is_true() {
+                    self.step()?;
+                    if let Flow::Return = self.block(body)? {
+                        return Ok(Flow::Return);
+                    }
+                }
+            },
+            Stmt::Return => return Ok(Flow::Return),
+            Stmt::Expr(expr) => {
+                self.eval(expr)?;
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<Val, String> {
+        match expr {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Var(name) => self.var(name),
+            Expr::Call(name, args) => {
+                let mut values = Vec::new();
+                for arg in args.iter() {
+                    values.push(self.eval(arg)?);
+                }
+                self.host.call(name, &values)
+            },
+            Expr::Not(expr) => Ok(Val::Bool(!self.eval(expr)?.is_true())),
+            Expr::Neg(expr) => Ok(Val::Int(self.eval(expr)?.as_int()?.wrapping_neg())),
+            Expr::And(left, right) => Ok(Val::Bool(self.eval(left)?.is_true() && self.eval(right)?.is_true())),
+            Expr::Or(left, right) => Ok(Val::Bool(self.eval(left)?.is_true() || self.eval(right)?.is_true())),
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.eval(left)?, self.eval(right)?);
+                binary(op, &left, &right)
+            }
+        }
+    }
+
+}
+
+fn binary(op: &str, left: &Val, right: &Val) -> Result<Val, String> {
+    match (op, left, right) {
+        ("==", l, r) => Ok(Val::Bool(l == r)),
+        ("!=", l, r) => Ok(Val::Bool(l != r)),
+        ("+", Val::Str(l), r) => Ok(Val::Str(format!("{}{}", l, r))),
+        ("+", l, Val::Str(r)) => Ok(Val::Str(format!("{}{}", l, r))),
+        (_, Val::Str(l), Val::Str(r)) if ["<", "<=", ">", ">="].contains(&op) => Ok(Val::Bool(compare(op, l.cmp(r)))),
+        _ => {
+            let (l, r) = (left.as_int()?, right.as_int()?);
+            match op {
+                "+" => Ok(Val::Int(l.wrapping_add(r))),
+                "-" => Ok(Val::Int(l.wrapping_sub(r))),
+                "*" => Ok(Val::Int(l.wrapping_mul(r))),
+                "/" | "%" if r == 0 => Err(String::from("division by zero")),
+                "/" => Ok(Val::Int(l.wrapping_div(r))),
+                "%" => Ok(Val::Int(l.wrapping_rem(r))),
+                _ => Ok(Val::Bool(compare(op, l.cmp(&r))))
+            }
+        }
+    }
+}
+
+fn compare(op: &str, ordering: std::cmp::Ordering) -> bool {
+    match op {
+        "<" => ordering.is_lt(),
+        "<=" => ordering.is_le(),
+        ">" => ordering.is_gt(),
+        _ => ordering.is_ge()
+    }
+}
+
+// A small scripting language: variables (let), if / else if / else, while, return,
+// arithmetic, comparisons, and, or, not, and calls to the functions given by the host
+#[derive(Clone)]
+pub struct Script {
+    source: String,
+    body: Vec<Stmt>
+}
+
+// The body is parsed from the source, so the source is enough to tell scripts apart
+impl PartialEq for Script {
+    fn eq(&self, other: &Script) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Script {
+
+    pub fn parse(source: &str) -> Result<Script, String> {
+        let mut parser = Parser { tokens: tokenize(source)?, pos: 0, depth: 0 };
+        let mut body = Vec::new();
+        while parser.peek().is_some() {
+            body.push(parser.statement()?);
+        }
+        Ok(Script { source: source.to_string(), body })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn run(&self, host: &mut dyn Host) -> Result<(), String> {
+        let mut run = Run { host, vars: Vec::new(), steps: 0 };
+        run.block(&self.body).map(|_| ())
+    }
+
+}
+
+// A script run when a verb is applied to a thing, instead of what the thing would do
+#[derive(Clone, PartialEq)]
+pub struct Behaviour {
+    pub thing: String,
+    pub verb: String,
+    pub script: Script
+}
+
+impl Behaviour {
+
+    pub fn list_from_data(table: &Table) -> Result<Vec<Behaviour>, String> {
+        let mut behaviours = Vec::new();
+        for behaviour in data::get_tables(table, "script").iter() {
+            let thing = data::get_str(behaviour, "thing")?;
+            let verb = data::get_str(behaviour, "verb")?;
+            if !VERBS.contains(&verb.as_str()) {
+                return Err(format!("Unknown verb '{}' in the script for '{}'", verb, thing));
+            }
+            let script = Script::parse(&data::get_str(behaviour, "code")?)
+                .map_err(|e| format!("Script for '{}' on '{}', {}", verb, thing, e))?;
+            behaviours.push(Behaviour { thing, verb, script });
+        }
+        Ok(behaviours)
+    }
+
+    pub fn list_to_data(behaviours: &[Behaviour]) -> Value {
+        Value::Array(behaviours.iter().map(|b| {
+            let mut table = Table::new();
+            table.insert(String::from("thing"), Value::String(b.thing.clone()));
+            table.insert(String::from("verb"), Value::String(b.verb.clone()));
+            table.insert(String::from("code"), Value::String(b.script.source().to_string()));
+            Value::Table(table)
+        }).collect())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keeps what the script says
+    struct Said(Vec<String>);
+
+    impl Host for Said {
+        fn call(&mut self, name: &str, args: &[Val]) -> Result<Val, String> {
+            match name {
+                "say" => {
+                    self.0.push(args.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(" "));
+                    Ok(Val::Bool(true))
+                },
+                _ => Err(format!("unknown function '{}'", name))
+            }
+        }
+    }
+
+    fn run(source: &str) -> Result<Vec<String>, String> {
+        let mut host = Said(Vec::new());
+        Script::parse(source)?.run(&mut host)?;
+        Ok(host.0)
+    }
+
+    #[test]
+    fn arithmetic_wraps_instead_of_panicking() {
+        let source = "let min = 0 - 9223372036854775807 - 1\nsay(-min, min / -1, min % -1)";
+        assert_eq!(run(source), Ok(vec!(String::from("-9223372036854775808 -9223372036854775808 0"))));
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        assert_eq!(run("say(1 / 0)"), Err(String::from("division by zero")));
+        assert_eq!(run("say(1 % 0)"), Err(String::from("division by zero")));
+    }
+
+    #[test]
+    fn nesting_within_the_limit_runs() {
+        let source = format!("say({}1{})", "(".repeat(20), ")".repeat(20));
+        assert_eq!(run(&source), Ok(vec!(String::from("1"))));
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let sources = [
+            format!("say({}1{})", "(".repeat(100000), ")".repeat(100000)),
+            format!("say(1{})", " + 1".repeat(100000)),
+            format!("say({}1)", "-".repeat(100000)),
+            format!("say({}true)", "not ".repeat(100000)),
+            format!("say(true{})", " and true".repeat(100000)),
+            format!("{}{}", "if true { ".repeat(100000), "}".repeat(100000))
+        ];
+        for source in sources.iter() {
+            let result = Script::parse(source).map(|_| ());
+            assert!(result.as_ref().err().map(|e| e.ends_with("too deeply nested")).unwrap_or(false), "{:?}", result);
+        }
+    }
+
+}
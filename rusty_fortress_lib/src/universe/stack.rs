@@ -0,0 +1,182 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::universe::base::Location;
+use crate::universe::base::Name;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::data;
+
+// How many units of a stackable item are in each place
+#[derive(Clone, PartialEq)]
+pub struct Stack {
+    pub plural: String,
+    counts: Vec<(Location, u32)>
+}
+
+impl Stack {
+
+    pub fn new(plural: &str, location: Location, count: u32) -> Stack {
+        Stack { plural: plural.to_string(), counts: vec!((location, count)) }
+    }
+
+    pub fn count(&self, location: &Location) -> u32 {
+        self.counts.iter().filter(|(l, _)| l == location).map(|(_, n)| *n).sum()
+    }
+
+    // Where the stack is for conditions and scope: with the player if any is carried
+    pub fn location(&self) -> Location {
+        if self.count(&Location::Inventory) > 0 {
+            return Location::Inventory;
+        }
+        self.counts.iter().find(|(_, n)| *n > 0).map(|(l, _)| l.clone()).unwrap_or(Location::Nowhere)
+    }
+
+    // Moves that many units from one place to another, all of them when no count is given.
+    // Returns how many were moved, none if there weren't enough
+    pub fn move_count(&mut self, from: &Location, to: Location, count: Option<u32>) -> u32 {
+        let available = self.count(from);
+        let count = count.unwrap_or(available);
+        if count == 0 || count > available {
+            return 0;
+        }
+        self.counts.iter_mut().filter(|(l, _)| l == from).for_each(|(_, n)| *n -= count);
+        match self.counts.iter_mut().find(|(l, _)| *l == to) {
+            Option::Some((_, n)) => *n += count,
+            Option::None => self.counts.push((to, count))
+        }
+        self.counts.retain(|(_, n)| *n > 0);
+        count
+    }
+
+    pub fn move_all(&mut self, to: Location) {
+        let total = self.counts.iter().map(|(_, n)| *n).sum();
+        self.counts = vec!((to, total));
+    }
+
+    // "12 arrows", "1 arrow"
+    pub fn counted(&self, name: &str, count: u32) -> String {
+        let name = if count == 1 { name } else { &self.plural };
+        message::text("thing.count", &Args::new().set_name(name).set_count(count as i64))
+    }
+
+    // A stack in one place is written as its location and count, one spread around as a
+    // table of counts by location
+    pub fn from_data(table: &Table, name: &str) -> Stack {
+        let plural = data::get_str_or(table, "plural", &format!("{}s", name));
+        let counts = table.get("counts").and_then(|c| c.as_table()).map(|counts| counts.iter()
+            .map(|(location, n)| (Location::from_data(location), n.as_integer().unwrap_or(0).max(0) as u32))
+            .filter(|(_, n)| *n > 0)
+            .collect::<Vec<(Location, u32)>>());
+        match counts {
+            Option::Some(counts) => Stack { plural, counts },
+            Option::None => {
+                let location = Location::from_data(&data::get_str_or(table, "location", ""));
+                let count = data::get_int(table, "count").unwrap_or(1).max(0) as u32;
+                Stack::new(&plural, location, count)
+            }
+        }
+    }
+
+    pub fn to_data(&self, table: &mut Table) {
+        table.insert(String::from("plural"), Value::String(self.plural.clone()));
+        match self.counts.as_slice() {
+            [(location, count)] => {
+                table.insert(String::from("location"), Value::String(location.to_data()));
+                table.insert(String::from("count"), Value::Integer(*count as i64));
+            },
+            _ => {
+                let counts = self.counts.iter().map(|(l, n)| (l.to_data(), Value::Integer(*n as i64))).collect();
+                table.insert(String::from("counts"), Value::Table(counts));
+            }
+        }
+    }
+
+}
+
+// Item coming in units counted rather than one thing each, like arrows or coins
+#[derive(Clone, PartialEq)]
+pub struct StackItem {
+    id: String,
+    name: String,
+    description: String,
+    stack: Stack
+}
+
+impl Name for StackItem {
+    fn name(&self) -> std::string::String {
+        String::from(&self.name)
+    }
+}
+
+impl HandleCommand for StackItem {
+    fn resolve_command(&mut self, command: &Command) -> String {
+        match command.keyword {
+            Option::Some(Keyword::View) => self.view(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
+        }
+    }
+}
+
+impl View for StackItem {
+    fn view(&mut self) -> String {
+        String::from(&self.description)
+    }
+}
+
+impl Thing for StackItem {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        self.stack.location()
+    }
+
+    fn is_at(&self, room: &str) -> bool {
+        self.stack.count(&Location::Room(room.to_string())) > 0
+    }
+
+    fn move_to(&mut self, location: Location) {
+        self.stack.move_all(location);
+    }
+
+    fn move_from(&mut self, from: &Location, to: Location) {
+        self.stack.move_count(from, to, Option::None);
+    }
+
+    fn stack(&self) -> Option<&Stack> {
+        Option::Some(&self.stack)
+    }
+
+    fn stack_mut(&mut self) -> Option<&mut Stack> {
+        Option::Some(&mut self.stack)
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = data::thing_table("stack", &self.id, &self.name, &self.description);
+        self.stack.to_data(&mut table);
+        table
+    }
+
+}
+
+impl StackItem {
+
+    pub fn from_data(table: &Table) -> Result<StackItem, String> {
+        let name = data::get_str(table, "name")?;
+        Ok(StackItem {
+            id: data::get_str(table, "id")?,
+            description: data::get_str_or(table, "description", "Nothing special about them."),
+            stack: Stack::from_data(table, &name),
+            name
+        })
+    }
+
+}
@@ -0,0 +1,211 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::message;
+use crate::app::parser::Keyword;
+use crate::app::tokenizer;
+use crate::universe::base::Location;
+use crate::universe::condition::Condition;
+use crate::universe::data;
+use crate::universe::vars::Compare;
+use crate::universe::vars::Var;
+
+// What sets a trigger off, written as "verb:thing", "enter:room", "turn:number" or
+// "say:words"
+#[derive(Clone, PartialEq)]
+pub enum When {
+    Verb(String, String),
+    Enter(String),
+    Turn(u32),
+    Say(String)
+}
+
+impl When {
+
+    fn from_data(text: &str) -> Result<When, String> {
+        match text.split_once(':') {
+            Option::Some(("enter", room)) => Ok(When::Enter(room.to_string())),
+            Option::Some(("say", words)) => Ok(When::Say(words.to_string())),
+            Option::Some(("turn", turn)) => match turn.parse::<u32>() {
+                Ok(turn) => Ok(When::Turn(turn)),
+                Err(_) => Err(format!("Invalid turn in '{}'", text))
+            },
+            Option::Some((verb, thing)) if VERBS.contains(&verb) => Ok(When::Verb(verb.to_string(), thing.to_string())),
+            Option::Some((verb, _)) => Err(format!("Unknown verb '{}' in '{}'", verb, text)),
+            Option::None => Err(format!("Unknown trigger event '{}'", text))
+        }
+    }
+
+    fn to_data(&self) -> String {
+        match self {
+            When::Verb(verb, thing) => format!("{}:{}", verb, thing),
+            When::Enter(room) => format!("enter:{}", room),
+            When::Turn(turn) => format!("turn:{}", turn),
+            When::Say(words) => format!("say:{}", words)
+        }
+    }
+
+    // Whether this is the event that happened. Words said match whatever the case, spacing
+    // and punctuation
+    pub fn is(&self, event: &When) -> bool {
+        match (self, event) {
+            (When::Say(words), When::Say(said)) => {
+                let ignore_accents = message::ignore_accents();
+                tokenizer::words(words, ignore_accents) == tokenizer::words(said, ignore_accents)
+            },
+            _ => self == event
+        }
+    }
+
+}
+
+// What a trigger does: "set:flag", "clear:flag", "var:name=value", "add:counter:amount",
+// "move:thing:location" or "state:thing:state"
+#[derive(Clone, PartialEq)]
+pub enum Action {
+    Set(String),
+    Clear(String),
+    Var(String, Var),
+    Add(String, i64),
+    Move(String, Location),
+    State(String, String)
+}
+
+impl Action {
+
+    fn from_data(text: &str) -> Result<Action, String> {
+        let (name, rest) = text.split_once(':').ok_or_else(|| format!("Unknown trigger action '{}'", text))?;
+        let pair = || rest.split_once(':').map(|(a, b)| (a.to_string(), b.to_string()))
+            .ok_or_else(|| format!("Trigger action '{}' needs a thing and a value", text));
+        match name {
+            "set" => Ok(Action::Set(rest.to_string())),
+            "clear" => Ok(Action::Clear(rest.to_string())),
+            "var" => match Compare::split(rest) {
+                Option::Some((name, Compare::Equal, value)) => Ok(Action::Var(name, value)),
+                _ => Err(format!("Trigger action '{}' needs a name and a value", text))
+            },
+            "add" => {
+                let (name, amount) = pair()?;
+                amount.parse::<i64>().map(|amount| Action::Add(name, amount))
+                    .map_err(|_| format!("Invalid amount in '{}'", text))
+            },
+            "move" => pair().map(|(thing, location)| Action::Move(thing, Location::from_data(&location))),
+            "state" => pair().map(|(thing, state)| Action::State(thing, state)),
+            _ => Err(format!("Unknown trigger action '{}'", text))
+        }
+    }
+
+    fn to_data(&self) -> String {
+        match self {
+            Action::Set(flag) => format!("set:{}", flag),
+            Action::Clear(flag) => format!("clear:{}", flag),
+            Action::Var(name, value) => format!("var:{}={}", name, value),
+            Action::Add(name, amount) => format!("add:{}:{}", name, amount),
+            Action::Move(thing, location) => format!("move:{}:{}", thing, location.to_data()),
+            Action::State(thing, state) => format!("state:{}:{}", thing, state)
+        }
+    }
+
+}
+
+// On an event, if the conditions hold, prints the message and runs the actions. A trigger on
+// a verb replaces what the verb would do
+#[derive(Clone, PartialEq)]
+pub struct Trigger {
+    pub when: When,
+    pub conditions: Vec<Condition>,
+    pub message: String,
+    pub actions: Vec<Action>,
+    pub once: bool,
+    pub fired: bool
+}
+
+impl Trigger {
+
+    pub fn is_ready(&self) -> bool {
+        !(self.once && self.fired)
+    }
+
+    fn from_data(table: &Table) -> Result<Trigger, String> {
+        let mut actions = Vec::new();
+        for action in data::get_str_list(table, "do").iter() {
+            actions.push(Action::from_data(action)?);
+        }
+        Ok(Trigger {
+            when: When::from_data(&data::get_str(table, "on")?)?,
+            conditions: Condition::list_from_data(&data::get_str_list(table, "if"))?,
+            message: data::get_str_or(table, "message", ""),
+            actions,
+            once: data::get_bool_or(table, "once", false),
+            fired: data::get_bool_or(table, "fired", false)
+        })
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = Table::new();
+        table.insert(String::from("on"), Value::String(self.when.to_data()));
+        table.insert(String::from("if"), data::str_list(&Condition::list_to_data(&self.conditions)));
+        table.insert(String::from("message"), Value::String(self.message.clone()));
+        let actions: Vec<String> = self.actions.iter().map(|a| a.to_data()).collect();
+        table.insert(String::from("do"), data::str_list(&actions));
+        table.insert(String::from("once"), Value::Boolean(self.once));
+        if self.once {
+            table.insert(String::from("fired"), Value::Boolean(self.fired));
+        }
+        table
+    }
+
+    pub fn list_from_data(table: &Table) -> Result<Vec<Trigger>, String> {
+        data::get_tables(table, "trigger").iter().map(Trigger::from_data).collect()
+    }
+
+    pub fn list_to_data(triggers: &[Trigger]) -> Value {
+        Value::Array(triggers.iter().map(|t| Value::Table(t.to_data())).collect())
+    }
+
+}
+
+// The verbs triggers and scripts can wait for, as the verb function names them
+pub const VERBS: [&str; 9] = ["open", "look", "take", "go", "light", "extinguish", "pull", "push", "drop"];
+
+// Name of a verb in the trigger events, for the verbs applied to a single thing
+pub fn verb(keyword: &Keyword) -> &'static str {
+    match keyword {
+        Keyword::Open | Keyword::OpenWith => "open",
+        Keyword::View => "look",
+        Keyword::Take => "take",
+        Keyword::GoThrough => "go",
+        Keyword::Light => "light",
+        Keyword::Extinguish => "extinguish",
+        Keyword::Pull => "pull",
+        Keyword::Push => "push",
+        Keyword::Drop => "drop",
+        _ => ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triggers(on: &str) -> Result<Vec<Trigger>, String> {
+        let world = format!("[[trigger]]\non = \"{}\"\nmessage = \"Click\"\n", on).parse::<Value>().unwrap();
+        Trigger::list_from_data(world.as_table().unwrap())
+    }
+
+    #[test]
+    fn reads_the_events() {
+        assert!(VERBS.iter().all(|verb| triggers(&format!("{}:lever", verb)).is_ok()));
+        assert!(triggers("enter:cellar").is_ok());
+        assert!(triggers("turn:20").is_ok());
+        assert!(triggers("say:open sesame").is_ok());
+    }
+
+    #[test]
+    fn turns_down_unknown_verbs() {
+        assert_eq!(triggers("yank:lever").err(), Option::Some(String::from("Unknown verb 'yank' in 'yank:lever'")));
+        assert_eq!(triggers("Pull:lever").err(), Option::Some(String::from("Unknown verb 'Pull' in 'Pull:lever'")));
+        assert!(triggers("turn:soon").is_err());
+        assert!(triggers("lever").is_err());
+    }
+
+}
@@ -0,0 +1,266 @@
+use std::cmp::Ordering;
+use std::fmt;
+use toml::Value;
+use toml::value::Table;
+use crate::universe::data;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Var {
+    Bool(bool),
+    Int(i64),
+    Str(String)
+}
+
+impl Var {
+
+    // "true" and "false" are flags, whole numbers are counters and anything else is text
+    pub fn parse(text: &str) -> Var {
+        match text {
+            "true" => Var::Bool(true),
+            "false" => Var::Bool(false),
+            _ => match text.parse::<i64>() {
+                Ok(n) => Var::Int(n),
+                Err(_) => Var::Str(text.to_string())
+            }
+        }
+    }
+
+    // Whether the variable counts as a raised flag
+    pub fn is_set(&self) -> bool {
+        match self {
+            Var::Bool(b) => *b,
+            Var::Int(n) => *n != 0,
+            Var::Str(s) => !s.is_empty()
+        }
+    }
+
+    fn from_data(value: &Value) -> Option<Var> {
+        match value {
+            Value::Boolean(b) => Option::Some(Var::Bool(*b)),
+            Value::Integer(n) => Option::Some(Var::Int(*n)),
+            Value::String(s) => Option::Some(Var::Str(s.clone())),
+            _ => Option::None
+        }
+    }
+
+    fn to_data(&self) -> Value {
+        match self {
+            Var::Bool(b) => Value::Boolean(*b),
+            Var::Int(n) => Value::Integer(*n),
+            Var::Str(s) => Value::String(s.clone())
+        }
+    }
+
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Var::Bool(b) => write!(f, "{}", b),
+            Var::Int(n) => write!(f, "{}", n),
+            Var::Str(s) => write!(f, "{}", s)
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Compare {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual
+}
+
+// Longest first, so "<=" is not read as "<"
+const COMPARE_SYMBOLS: [(&str, Compare); 6] = [
+    ("!=", Compare::NotEqual), ("<=", Compare::LessOrEqual), (">=", Compare::GreaterOrEqual),
+    ("=", Compare::Equal), ("<", Compare::Less), (">", Compare::Greater)
+];
+
+impl Compare {
+
+    // Splits "name<op>value" into its three parts
+    pub fn split(text: &str) -> Option<(String, Compare, Var)> {
+        let (idx, symbol, compare) = COMPARE_SYMBOLS.iter()
+            .filter_map(|(symbol, compare)| text.find(symbol).map(|idx| (idx, *symbol, *compare)))
+            .min_by_key(|(idx, symbol, _)| (*idx, usize::MAX - symbol.len()))?;
+        let name = text[..idx].trim();
+        if name.is_empty() {
+            return Option::None;
+        }
+        Option::Some((name.to_string(), compare, Var::parse(text[idx + symbol.len()..].trim())))
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        COMPARE_SYMBOLS.iter().find(|(_, c)| c == self).map(|(symbol, _)| *symbol).unwrap_or("=")
+    }
+
+    // Counters compare as numbers and everything else only as equal or not
+    pub fn holds(&self, value: &Var, other: &Var) -> bool {
+        let order = match (value, other) {
+            (Var::Int(a), Var::Int(b)) => a.cmp(b),
+            _ if value == other => Ordering::Equal,
+            _ => return *self == Compare::NotEqual
+        };
+        match self {
+            Compare::Equal => order == Ordering::Equal,
+            Compare::NotEqual => order != Ordering::Equal,
+            Compare::Less => order == Ordering::Less,
+            Compare::LessOrEqual => order != Ordering::Greater,
+            Compare::Greater => order == Ordering::Greater,
+            Compare::GreaterOrEqual => order != Ordering::Less
+        }
+    }
+
+}
+
+// The flags, counters and texts of the world, kept in the order they were first set
+#[derive(Clone, PartialEq, Default)]
+pub struct Vars {
+    values: Vec<(String, Var)>
+}
+
+impl Vars {
+
+    pub fn get(&self, name: &str) -> Option<&Var> {
+        self.values.iter().find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    pub fn set(&mut self, name: &str, value: Var) {
+        match self.values.iter_mut().find(|(n, _)| n == name) {
+            Option::Some((_, v)) => *v = value,
+            Option::None => self.values.push((name.to_string(), value))
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.values.retain(|(n, _)| n != name);
+    }
+
+    pub fn is_set(&self, name: &str) -> bool {
+        self.get(name).map(|v| v.is_set()).unwrap_or(false)
+    }
+
+    // Adds to a counter, a missing one starting at zero. A counter that would overflow is
+    // left as it is
+    pub fn add(&mut self, name: &str, amount: i64) -> Result<(), String> {
+        let value = match self.get(name) {
+            Option::Some(Var::Int(n)) => *n,
+            Option::Some(Var::Bool(b)) => *b as i64,
+            Option::Some(Var::Str(_)) => return Err(format!("'{}' is not a counter", name)),
+            Option::None => 0
+        };
+        let sum = value.checked_add(amount).ok_or_else(|| format!("'{}' would overflow", name))?;
+        self.set(name, Var::Int(sum));
+        Ok(())
+    }
+
+    pub fn values(&self) -> &[(String, Var)] {
+        &self.values
+    }
+
+    // Reads the "vars" table, and the "flags" list of older worlds and saved games
+    pub fn from_data(table: &Table) -> Vars {
+        let mut vars = Vars::default();
+        data::get_str_list(table, "flags").iter().for_each(|flag| vars.set(flag, Var::Bool(true)));
+        if let Option::Some(values) = table.get("vars").and_then(|v| v.as_table()) {
+            for (name, value) in values.iter() {
+                if let Option::Some(value) = Var::from_data(value) {
+                    vars.set(name, value);
+                }
+            }
+        }
+        vars
+    }
+
+    pub fn to_data(&self, table: &mut Table) {
+        let values = self.values.iter().map(|(name, value)| (name.clone(), value.to_data())).collect();
+        table.insert(String::from("vars"), Value::Table(values));
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holds(condition: &str, value: Var) -> bool {
+        let (_, compare, other) = Compare::split(condition).unwrap();
+        compare.holds(&value, &other)
+    }
+
+    #[test]
+    fn parses_flags_counters_and_texts() {
+        assert_eq!(Var::parse("true"), Var::Bool(true));
+        assert_eq!(Var::parse("false"), Var::Bool(false));
+        assert_eq!(Var::parse("-12"), Var::Int(-12));
+        assert_eq!(Var::parse("12 apples"), Var::Str(String::from("12 apples")));
+        assert_eq!(Var::parse("True"), Var::Str(String::from("True")));
+    }
+
+    #[test]
+    fn splits_at_the_longest_symbol() {
+        let split = |text: &str| Compare::split(text).map(|(name, compare, value)| (name, compare.symbol(), value));
+        assert_eq!(split("gold>=10"), Option::Some((String::from("gold"), ">=", Var::Int(10))));
+        assert_eq!(split("gold <= 10"), Option::Some((String::from("gold"), "<=", Var::Int(10))));
+        assert_eq!(split("mood != angry"), Option::Some((String::from("mood"), "!=", Var::Str(String::from("angry")))));
+        assert_eq!(split("depth<-2"), Option::Some((String::from("depth"), "<", Var::Int(-2))));
+        assert_eq!(split("door=open"), Option::Some((String::from("door"), "=", Var::Str(String::from("open")))));
+        assert_eq!(split("=3"), Option::None);
+        assert_eq!(split("gold"), Option::None);
+    }
+
+    #[test]
+    fn compares_counters_as_numbers() {
+        assert!(holds("gold=10", Var::Int(10)));
+        assert!(holds("gold!=10", Var::Int(9)));
+        assert!(holds("gold<10", Var::Int(9)));
+        assert!(!holds("gold<10", Var::Int(10)));
+        assert!(holds("gold<=10", Var::Int(10)));
+        assert!(holds("gold>-1", Var::Int(0)));
+        assert!(!holds("gold>10", Var::Int(10)));
+        assert!(holds("gold>=10", Var::Int(10)));
+        assert!(holds("gold>9", Var::Int(10)));
+    }
+
+    #[test]
+    fn compares_flags_and_texts_only_as_equal_or_not() {
+        assert!(holds("mood=angry", Var::Str(String::from("angry"))));
+        assert!(holds("mood!=angry", Var::Str(String::from("calm"))));
+        assert!(!holds("mood<angry", Var::Str(String::from("calm"))));
+        assert!(!holds("mood>angry", Var::Str(String::from("calm"))));
+        assert!(holds("mood<=angry", Var::Str(String::from("angry"))));
+        assert!(holds("lever=true", Var::Bool(true)));
+        assert!(!holds("lever=true", Var::Int(1)));
+        assert!(holds("lever!=1", Var::Bool(true)));
+        assert!(!holds("count<5", Var::Str(String::from("3"))));
+    }
+
+    #[test]
+    fn adds_to_counters() {
+        let mut vars = Vars::default();
+        vars.add("gold", 5).unwrap();
+        vars.add("gold", -2).unwrap();
+        assert_eq!(vars.get("gold"), Option::Some(&Var::Int(3)));
+        vars.set("lever", Var::Bool(true));
+        vars.add("lever", 1).unwrap();
+        assert_eq!(vars.get("lever"), Option::Some(&Var::Int(2)));
+        vars.set("mood", Var::Str(String::from("calm")));
+        assert!(vars.add("mood", 1).is_err());
+        assert!(vars.is_set("gold") && vars.is_set("lever") && !vars.is_set("missing"));
+    }
+
+    #[test]
+    fn a_counter_never_overflows() {
+        let mut vars = Vars::default();
+        vars.add("x", i64::MAX).unwrap();
+        assert_eq!(vars.add("x", i64::MAX), Err(String::from("'x' would overflow")));
+        assert_eq!(vars.get("x"), Option::Some(&Var::Int(i64::MAX)));
+        vars.add("y", i64::MIN).unwrap();
+        assert!(vars.add("y", -1).is_err());
+        assert_eq!(vars.get("y"), Option::Some(&Var::Int(i64::MIN)));
+    }
+
+}
@@ -0,0 +1,108 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::universe::base::Location;
+use crate::universe::base::Name;
+use crate::universe::base::Take;
+use crate::universe::base::Thing;
+use crate::universe::base::View;
+use crate::universe::combat;
+use crate::universe::data;
+
+// Weapon
+#[derive(Clone, PartialEq)]
+pub struct Weapon {
+    id: String,
+    name: String,
+    description: String,
+    location: Location,
+    damage: (u32, u32)
+}
+
+impl Name for Weapon {
+    fn name(&self) -> std::string::String {
+        String::from(&self.name)
+    }
+}
+
+impl HandleCommand for Weapon {
+    fn resolve_command(&mut self, command: &Command) -> String {
+        match command.keyword {
+            Option::Some(Keyword::Take) => self.take(),
+            Option::Some(Keyword::View) => self.view(),
+            _ => DefaultCommandHandler::new().resolve_command(command)
+        }
+    }
+}
+
+impl Take for Weapon {
+    fn take(&mut self) -> String {
+        if self.location == Location::Inventory {
+            message::text("take.already", &Args::new())
+        }
+        else {
+            self.location = Location::Inventory;
+            message::named("take.done", &self.name)
+        }
+    }
+}
+
+impl View for Weapon {
+    fn view(&mut self) -> String {
+        String::from(&self.description)
+    }
+}
+
+impl Thing for Weapon {
+
+    fn id(&self) -> String {
+        String::from(&self.id)
+    }
+
+    fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn move_to(&mut self, location: Location) {
+        self.location = location;
+    }
+
+    fn damage(&self) -> Option<(u32, u32)> {
+        Option::Some(self.damage)
+    }
+
+    fn to_data(&self) -> Table {
+        let mut table = data::thing_table("weapon", &self.id, &self.name, &self.description);
+        table.insert(String::from("location"), Value::String(self.location.to_data()));
+        table.insert(String::from("damage"), combat::damage_to_data(self.damage));
+        table
+    }
+
+}
+
+impl Weapon {
+
+    pub fn new(id: &str, name: &str, location: Location, damage: (u32, u32)) -> Weapon {
+        Weapon {
+            id: String::from(id),
+            name: String::from(name),
+            description: String::from("It looks dangerous."),
+            location,
+            damage
+        }
+    }
+
+    pub fn from_data(table: &Table) -> Result<Weapon, String> {
+        let location = Location::from_data(&data::get_str_or(table, "location", ""));
+        let damage = combat::damage_from_data(table)?.ok_or_else(|| String::from("A weapon needs a damage range"))?;
+        let mut weapon = Weapon::new(&data::get_str(table, "id")?, &data::get_str(table, "name")?, location, damage);
+        weapon.description = data::get_str_or(table, "description", &weapon.description);
+        Ok(weapon)
+    }
+
+}
@@ -0,0 +1,1102 @@
+use toml::Value;
+use toml::value::Table;
+use crate::app::command::DefaultCommandHandler;
+use crate::app::command::Command;
+use crate::app::command::HandleCommand;
+use crate::app::message;
+use crate::app::message::Args;
+use crate::app::parser::Keyword;
+use crate::app::tokenizer;
+use crate::universe::base::Location;
+use crate::universe::base::Thing;
+use crate::universe::clock::Clock;
+use crate::universe::clock::Event;
+use crate::universe::combat;
+use crate::universe::combat::Stats;
+use crate::universe::condition::Condition;
+use crate::universe::condition::Test;
+use crate::universe::creature::Creature;
+use crate::universe::data;
+use crate::universe::hint::Hint;
+use crate::universe::describe;
+use crate::universe::dialogue::Choice;
+use crate::universe::dialogue::Node;
+use crate::universe::door::Door;
+use crate::universe::door::LockedDoor;
+use crate::universe::item::Item;
+use crate::universe::light::LightSource;
+use crate::universe::npc::Npc;
+use crate::universe::rng::Rng;
+use crate::universe::room::Room;
+use crate::universe::score::Ending;
+use crate::universe::score::Milestone;
+use crate::universe::score::Outcome;
+use crate::universe::score::Score;
+use crate::universe::script::Behaviour;
+use crate::universe::script::Host;
+use crate::universe::script::Script;
+use crate::universe::script::Val;
+use crate::universe::stack::StackItem;
+use crate::universe::vars::Var;
+use crate::universe::vars::Vars;
+use crate::universe::trigger;
+use crate::universe::trigger::Action;
+use crate::universe::trigger::Trigger;
+use crate::universe::trigger::When;
+use crate::universe::weapon::Weapon;
+
+fn thing_from_data(table: &Table) -> Result<Box<dyn Thing>, String> {
+    let kind = data::get_str(table, "kind")?;
+    match kind.as_str() {
+        "door" => Ok(Box::new(Door::from_data(table)?)),
+        "locked_door" => Ok(Box::new(LockedDoor::from_data(table)?)),
+        "item" => Ok(Box::new(Item::from_data(table)?)),
+        "light" => Ok(Box::new(LightSource::from_data(table)?)),
+        "npc" => Ok(Box::new(Npc::from_data(table)?)),
+        "creature" => Ok(Box::new(Creature::from_data(table)?)),
+        "weapon" => Ok(Box::new(Weapon::from_data(table)?)),
+        "stack" => Ok(Box::new(StackItem::from_data(table)?)),
+        _ => Err(format!("Unknown kind of thing '{}'", kind))
+    }
+}
+
+fn matches(thing: &dyn Thing, text: &str) -> bool {
+    let name = tokenizer::normalise(&thing.name(), message::ignore_accents());
+    let text = tokenizer::normalise(text, message::ignore_accents());
+    let plural = thing.stack().map(|s| tokenizer::normalise(&s.plural, message::ignore_accents()));
+    let named = |name: &str| name == text || name.ends_with(&format!(" {}", text));
+    named(&name) || plural.map(|p| named(&p)).unwrap_or(false) || thing.id() == text
+}
+
+fn missing_object_message(keyword: &Keyword) -> String {
+    let id = match keyword {
+        Keyword::OpenWith => Keyword::Open.id(),
+        Keyword::AttackWith => Keyword::Attack.id(),
+        Keyword::Open | Keyword::Take | Keyword::GoThrough | Keyword::Light | Keyword::Extinguish |
+        Keyword::Talk | Keyword::Attack | Keyword::Pull | Keyword::Push | Keyword::Drop => keyword.id(),
+        _ => Keyword::View.id()
+    };
+    message::text(&format!("missing.{}", id), &Args::new())
+}
+
+fn script_arg(args: &[Val], idx: usize) -> Result<String, String> {
+    args.get(idx).map(|v| v.to_string()).ok_or_else(|| format!("missing argument {}", idx + 1))
+}
+
+// The numbers a script's random(min, max) asks for, within what the generator gives rather
+// than cut down to their lowest bits
+fn random_range(min: i64, max: i64) -> (u32, u32) {
+    let clamp = |n: i64| n.clamp(0, u32::MAX as i64) as u32;
+    (clamp(min), clamp(max.max(min)))
+}
+
+// The world as seen from a script run on a thing
+struct ScriptHost<'a> {
+    world: &'a mut World,
+    idx: usize,
+    command: Command,
+    output: Vec<String>
+}
+
+impl<'a> ScriptHost<'a> {
+
+    fn thing(&mut self, id: &str) -> Result<&mut Box<dyn Thing>, String> {
+        self.world.things.iter_mut().find(|t| t.id() == id).ok_or_else(|| format!("unknown thing '{}'", id))
+    }
+
+}
+
+impl<'a> Host for ScriptHost<'a> {
+
+    fn call(&mut self, name: &str, args: &[Val]) -> Result<Val, String> {
+        let arg = |idx| script_arg(args, idx);
+        let done = Ok(Val::Bool(true));
+        match name {
+            "say" => {
+                self.output.push(args.iter().map(|a| a.to_string()).collect::<Vec<String>>().join(""));
+                done
+            },
+            "this" => Ok(Val::Str(self.world.things[self.idx].id())),
+            "verb" => Ok(Val::Str(self.command.keyword.as_ref().map(trigger::verb).unwrap_or("").to_string())),
+            "object" => Ok(Val::Str(self.command.objects.first().cloned().unwrap_or_default())),
+            "here" => Ok(Val::Str(self.world.location.clone())),
+            "turn" => Ok(Val::Int(self.world.clock.turn() as i64)),
+            "has" => Ok(Val::Bool(self.world.holds(&Condition { negated: false, test: Test::Has(arg(0)?) }))),
+            "flag" => Ok(Val::Bool(self.world.vars.is_set(&arg(0)?))),
+            "set" => {
+                self.world.set_flag(&arg(0)?);
+                done
+            },
+            "clear" => {
+                self.world.vars.remove(&arg(0)?);
+                done
+            },
+            "var" => Ok(match self.world.vars.get(&arg(0)?) {
+                Option::Some(Var::Bool(b)) => Val::Bool(*b),
+                Option::Some(Var::Int(n)) => Val::Int(*n),
+                Option::Some(Var::Str(s)) => Val::Str(s.clone()),
+                Option::None => Val::Bool(false)
+            }),
+            "set_var" => {
+                let value = match args.get(1) {
+                    Option::Some(Val::Bool(b)) => Var::Bool(*b),
+                    Option::Some(Val::Int(n)) => Var::Int(*n),
+                    Option::Some(Val::Str(s)) => Var::Str(s.clone()),
+                    Option::None => return Err(String::from("missing argument 2"))
+                };
+                self.world.vars.set(&arg(0)?, value);
+                done
+            },
+            "name" => Ok(Val::Str(self.thing(&arg(0)?)?.name())),
+            "state" => Ok(Val::Str(self.thing(&arg(0)?)?.state().unwrap_or_default())),
+            "set_state" => {
+                let state = arg(1)?;
+                Ok(Val::Bool(self.thing(&arg(0)?)?.set_state(&state)))
+            },
+            "location" => Ok(Val::Str(self.thing(&arg(0)?)?.location().to_data())),
+            "move" => {
+                let location = Location::from_data(&arg(1)?);
+                self.thing(&arg(0)?)?.move_to(location);
+                done
+            },
+            "go" => {
+                let room = arg(0)?;
+                if !self.world.rooms.iter().any(|r| r.id == room) {
+                    return Err(format!("unknown room '{}'", room));
+                }
+                self.world.location = room;
+                let text = self.world.enter();
+                self.output.push(text);
+                done
+            },
+            "random" => {
+                let (min, max) = (args.first().map(|a| a.as_int()).unwrap_or(Ok(1))?, args.get(1).map(|a| a.as_int()).unwrap_or(Ok(6))?);
+                let (min, max) = random_range(min, max);
+                Ok(Val::Int(self.world.rng.range(min, max) as i64))
+            },
+            // What the thing would have done without the script
+            "default" => {
+                let keyword = self.command.keyword.clone().unwrap_or(Keyword::View);
+                let text = self.world.object_behaviour(self.idx, &keyword, &self.command);
+                self.output.push(text);
+                done
+            },
+            _ => Err(format!("unknown function '{}'", name))
+        }
+    }
+
+}
+
+#[derive(Clone, PartialEq)]
+pub struct World {
+    title: String,
+    rooms: Vec<Room>,
+    things: Vec<Box<dyn Thing>>,
+    location: String,
+    player: Stats,
+    rng: Rng,
+    clock: Clock,
+    vars: Vars,
+    verbose: bool,
+    messages: Vec<(String, String)>,
+    conversation: Option<(String, String)>,
+    score: Score,
+    triggers: Vec<Trigger>,
+    behaviours: Vec<Behaviour>,
+    hints: Vec<Hint>
+}
+
+impl World {
+
+    pub fn load(text: &str) -> Result<World, String> {
+        match text.parse::<Value>() {
+            Ok(value) => World::from_data(&value),
+            Err(e) => Err(format!("Invalid world file: {}", e))
+        }
+    }
+
+    pub fn from_data(value: &Value) -> Result<World, String> {
+        let table = value.as_table().ok_or_else(|| String::from("A world must be a table"))?;
+        let mut rooms = Vec::new();
+        for room in data::get_tables(table, "room").iter() {
+            rooms.push(Room::from_data(room)?);
+        }
+        let mut things = Vec::new();
+        for thing in data::get_tables(table, "thing").iter() {
+            things.push(thing_from_data(thing)?);
+        }
+        let player = table.get("player").and_then(|p| p.as_table()).cloned().unwrap_or_default();
+        let location = data::get_str(&player, "location")?;
+        if !rooms.iter().any(|r| r.id == location) {
+            return Err(format!("Unknown room '{}'", location));
+        }
+        let messages = message::from_data(table)?;
+        let conversation = table.get("conversation").and_then(|c| c.as_table())
+            .map(|c| (data::get_str_or(c, "npc", ""), data::get_str_or(c, "node", "")));
+        Ok(World {
+            title: data::get_str_or(table, "title", "Rusty Fortress"),
+            rooms,
+            things,
+            location,
+            player: Stats::from_data(&player, &Stats::new(20, 2, 1, (1, 2)))?,
+            rng: data::get_int(table, "rng").map(|state| Rng::new(state as u64)).unwrap_or_else(Rng::from_time),
+            clock: Clock::from_data(table)?,
+            vars: Vars::from_data(table),
+            verbose: data::get_bool_or(table, "verbose", false),
+            messages,
+            conversation,
+            score: Score::from_data(table)?,
+            triggers: Trigger::list_from_data(table)?,
+            behaviours: Behaviour::list_from_data(table)?,
+            hints: Hint::list_from_data(table)?
+        })
+    }
+
+    pub fn to_data(&self) -> Value {
+        let mut player = Table::new();
+        player.insert(String::from("location"), Value::String(self.location.clone()));
+        self.player.to_data(&mut player);
+
+        let mut table = Table::new();
+        table.insert(String::from("title"), Value::String(self.title.clone()));
+        table.insert(String::from("player"), Value::Table(player));
+        table.insert(String::from("room"), Value::Array(self.rooms.iter().map(|r| Value::Table(r.to_data())).collect()));
+        table.insert(String::from("thing"), Value::Array(self.things.iter().map(|t| Value::Table(t.to_data())).collect()));
+        self.clock.to_data(&mut table);
+        table.insert(String::from("rng"), Value::Integer(self.rng.state() as i64));
+        self.vars.to_data(&mut table);
+        table.insert(String::from("verbose"), Value::Boolean(self.verbose));
+        message::to_data(&self.messages, &mut table);
+        self.score.to_data(&mut table);
+        table.insert(String::from("trigger"), Trigger::list_to_data(&self.triggers));
+        table.insert(String::from("script"), Behaviour::list_to_data(&self.behaviours));
+        table.insert(String::from("hint"), Hint::list_to_data(&self.hints));
+        if let Option::Some((npc, node)) = &self.conversation {
+            let mut conversation = Table::new();
+            conversation.insert(String::from("npc"), Value::String(npc.clone()));
+            conversation.insert(String::from("node"), Value::String(node.clone()));
+            table.insert(String::from("conversation"), Value::Table(conversation));
+        }
+        Value::Table(table)
+    }
+
+    // Restarts the random sequence so the same commands give the same game
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn title(&self) -> String {
+        String::from(&self.title)
+    }
+
+    // The messages of this world replacing those of the locale
+    pub fn messages(&self) -> &[(String, String)] {
+        &self.messages
+    }
+
+    pub fn room_name(&self) -> String {
+        self.room().name.clone()
+    }
+
+    fn room(&self) -> &Room {
+        self.rooms.iter().find(|r| r.id == self.location).unwrap()
+    }
+
+    fn is_visible(&self, thing: &dyn Thing) -> bool {
+        thing.location() == Location::Inventory || thing.is_at(&self.location)
+    }
+
+    fn is_lit(&self) -> bool {
+        !self.room().dark || self.things.iter().any(|t| t.gives_light() && self.is_visible(t.as_ref()))
+    }
+
+    // In the dark only the carried things can be reached
+    fn in_scope(&self, thing: &dyn Thing) -> bool {
+        thing.location() == Location::Inventory || (self.is_lit() && thing.is_at(&self.location))
+    }
+
+    fn find(&self, text: &str) -> Option<usize> {
+        self.things.iter().position(|t| self.in_scope(t.as_ref()) && matches(t.as_ref(), text))
+    }
+
+    // Names of the things the player can refer to right now
+    pub fn names_in_scope(&self) -> Vec<String> {
+        self.things.iter()
+            .filter(|t| self.in_scope(t.as_ref()))
+            .map(|t| t.name().to_lowercase())
+            .collect()
+    }
+
+    pub fn carried_names(&self) -> Vec<String> {
+        self.things.iter()
+            .filter(|t| t.location() == Location::Inventory)
+            .map(|t| t.name().to_lowercase())
+            .collect()
+    }
+
+    fn not_found_message(&self, text: &str) -> String {
+        if self.is_lit() { message::named("not_found", text) } else { message::text("too_dark", &Args::new()) }
+    }
+
+    fn describe(&self, brief: bool) -> String {
+        if !self.is_lit() {
+            return message::text("room.dark", &Args::new());
+        }
+        let room = self.room();
+        let things: Vec<&dyn Thing> = self.things.iter().filter(|t| t.is_at(&room.id)).map(|t| t.as_ref()).collect();
+        describe::room(room, &things, &self.rooms, brief)
+    }
+
+    pub fn look(&self) -> String {
+        self.describe(false)
+    }
+
+    // Describes the room the player has just got into, in full only the first time it is
+    // seen unless in verbose mode
+    pub fn enter(&mut self) -> String {
+        let brief = !self.verbose && self.room().visited;
+        let text = self.describe(brief);
+        if self.is_lit() {
+            let location = self.location.clone();
+            self.rooms.iter_mut().filter(|r| r.id == location).for_each(|r| r.visited = true);
+        }
+        text
+    }
+
+    pub fn set_verbose(&mut self, verbose: bool) -> String {
+        self.verbose = verbose;
+        message::text(if verbose { "verbose.on" } else { "verbose.off" }, &Args::new())
+    }
+
+    fn inventory(&self) -> String {
+        let names: Vec<String> = self.things.iter()
+            .filter(|t| t.location() == Location::Inventory)
+            .map(|t| match t.stack() {
+                Option::Some(stack) => stack.counted(&t.name(), stack.count(&Location::Inventory)),
+                Option::None => t.name()
+            })
+            .collect();
+        if names.is_empty() {
+            self.default_message(Keyword::Inventory)
+        }
+        else {
+            message::text("inventory", &Args::new().set("list", &names.join(", ")))
+        }
+    }
+
+    // Shows the variables of the world, or just the one named
+    fn debug(&self, command: &Command) -> String {
+        let values: Vec<String> = self.vars.values().iter()
+            .filter(|(name, _)| command.objects.is_empty() || command.objects.contains(name))
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect();
+        match command.objects.first() {
+            Option::Some(name) if values.is_empty() => format!("'{}' is not set", name),
+            _ if values.is_empty() => self.default_message(Keyword::Debug),
+            _ => values.join("\n")
+        }
+    }
+
+    fn go_through(&mut self, idx: usize, command: &Command) -> String {
+        match self.things[idx].exit_from(&self.location) {
+            Option::Some(room) => {
+                self.location = room.clone();
+                let text = format!("{}\n{}", message::text("go.through", &Args::new().set_name(&self.things[idx].name())), self.enter());
+                match self.fire_triggers(&When::Enter(room)) {
+                    Option::Some(triggered) => format!("{}\n{}", text, triggered),
+                    Option::None => text
+                }
+            },
+            Option::None => self.things[idx].resolve_command(command)
+        }
+    }
+
+    fn default_message(&self, keyword: Keyword) -> String {
+        DefaultCommandHandler::new().resolve_command(&Command {
+            status: true,
+            keyword: Option::Some(keyword),
+            objects: Vec::new(),
+            spans: Vec::new(),
+            literal: Option::None,
+            quantity: Option::None,
+            error: Option::None
+        })
+    }
+
+    pub fn holds(&self, condition: &Condition) -> bool {
+        let result = match &condition.test {
+            Test::Flag(name) => self.vars.is_set(name),
+            Test::Has(item) => self.things.iter().any(|t| t.id() == *item && t.location() == Location::Inventory),
+            Test::In(room) => self.location == *room,
+            Test::State(thing, state) => self.things.iter().any(|t| t.id() == *thing && t.state().as_ref() == Option::Some(state)),
+            Test::Var(name, compare, value) => self.vars.get(name).map(|v| compare.holds(v, value)).unwrap_or(false)
+        };
+        result != condition.negated
+    }
+
+    fn all_hold(&self, conditions: &[Condition]) -> bool {
+        conditions.iter().all(|c| self.holds(c))
+    }
+
+    // Scores the milestones reached on this turn
+    fn update_score(&mut self) -> Vec<String> {
+        let reached: Vec<Milestone> = self.score.milestones.iter()
+            .filter(|m| !self.score.is_scored(&m.id) && self.all_hold(&m.conditions))
+            .cloned()
+            .collect();
+        reached.iter().map(|m| {
+            self.score.achieve(&m.id);
+            let points = message::text("score.up", &Args::new().set_count(m.points as i64));
+            if m.message.is_empty() { points } else { format!("{}\n{}", m.message, points) }
+        }).collect()
+    }
+
+    fn score_args(&self) -> Args {
+        Args::new()
+            .set_count(self.clock.turn() as i64)
+            .set("points", &self.score.points().to_string())
+            .set("max", &self.score.max_points().to_string())
+    }
+
+    fn score(&self) -> String {
+        let id = if self.score.max_points() == 0 { "score.turns" } else { "score.points" };
+        message::text(id, &self.score_args())
+    }
+
+    // The end of the game, once the conditions of a victory or a defeat hold
+    pub fn outcome(&self) -> Option<Outcome> {
+        let reached = |endings: &[Ending]| endings.iter()
+            .find(|e| !e.conditions.is_empty() && self.all_hold(&e.conditions))
+            .map(|e| e.message.clone());
+        if let Option::Some(message) = reached(&self.score.victories) {
+            return Option::Some(Outcome::Victory(message));
+        }
+        reached(&self.score.defeats).map(Outcome::Defeat)
+    }
+
+    // The next hint for the first puzzle whose conditions hold
+    pub fn hint(&mut self) -> String {
+        let ready: Vec<bool> = self.hints.iter().map(|h| self.all_hold(&h.conditions)).collect();
+        let next = self.hints.iter_mut().zip(ready).filter(|(_, ready)| *ready).find_map(|(hint, _)| {
+            let count = hint.texts.len();
+            hint.reveal().map(|(number, text)| (number, count, text.to_string()))
+        });
+        match next {
+            Option::Some((number, count, text)) => {
+                let args = Args::new().set("number", &number.to_string()).set_count(count as i64).set("hint", &text);
+                message::text("hint.next", &args)
+            },
+            Option::None => message::text("hint.none", &Args::new())
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let id = if self.score.max_points() == 0 { "summary.turns" } else { "summary.points" };
+        message::text(id, &self.score_args())
+    }
+
+    fn run_action(&mut self, action: &Action) {
+        match action {
+            Action::Set(flag) => self.set_flag(flag),
+            Action::Clear(flag) => self.vars.remove(flag),
+            Action::Var(name, value) => self.vars.set(name, value.clone()),
+            // Adding to a text leaves it as it is
+            Action::Add(name, amount) => self.vars.add(name, *amount).unwrap_or_default(),
+            Action::Move(id, location) => if let Option::Some(thing) = self.things.iter_mut().find(|t| t.id() == *id) {
+                thing.move_to(location.clone());
+            },
+            Action::State(id, state) => if let Option::Some(thing) = self.things.iter_mut().find(|t| t.id() == *id) {
+                thing.set_state(state);
+            }
+        }
+    }
+
+    // Fires the triggers waiting for the event. Returns their messages, if any fired
+    fn fire_triggers(&mut self, when: &When) -> Option<String> {
+        let ready: Vec<usize> = (0..self.triggers.len())
+            .filter(|i| self.triggers[*i].when.is(when) && self.triggers[*i].is_ready() && self.all_hold(&self.triggers[*i].conditions))
+            .collect();
+        if ready.is_empty() {
+            return Option::None;
+        }
+        let mut messages = Vec::new();
+        for idx in ready {
+            // Only those firing once remember it, as saved games do
+            if self.triggers[idx].once {
+                self.triggers[idx].fired = true;
+            }
+            let trigger = self.triggers[idx].clone();
+            trigger.actions.iter().for_each(|action| self.run_action(action));
+            if !trigger.message.is_empty() {
+                messages.push(trigger.message);
+            }
+        }
+        Option::Some(messages.join("\n"))
+    }
+
+    // Says the text between quotes, or the words after the verb when there are no quotes.
+    // Triggers waiting for those words fire, whatever the case or punctuation
+    fn say(&mut self, command: &Command) -> String {
+        let text = command.literal.clone().unwrap_or_else(|| command.objects.join(" "));
+        if text.is_empty() {
+            return message::text("missing.say", &Args::new());
+        }
+        match self.fire_triggers(&When::Say(text.clone())) {
+            Option::Some(said) => said,
+            Option::None => message::text("say.done", &Args::new().set("text", &text))
+        }
+    }
+
+    // What the player can say to set a trigger off right now
+    pub fn passwords(&self) -> Vec<String> {
+        self.triggers.iter()
+            .filter(|t| t.is_ready() && self.all_hold(&t.conditions))
+            .filter_map(|t| match &t.when {
+                When::Say(text) => Option::Some(text.clone()),
+                _ => Option::None
+            })
+            .collect()
+    }
+
+    fn set_flag(&mut self, name: &str) {
+        self.vars.set(name, Var::Bool(true));
+    }
+
+    fn conversation_node(&self) -> Option<(usize, Node)> {
+        let (npc, node) = self.conversation.as_ref()?;
+        let idx = self.things.iter().position(|t| t.id() == *npc)?;
+        let node = self.things[idx].dialogue()?.node(node)?.clone();
+        Option::Some((idx, node))
+    }
+
+    fn available_choices(&self, node: &Node) -> Vec<Choice> {
+        node.choices.iter()
+            .filter(|c| c.conditions.iter().all(|cond| self.holds(cond)))
+            .cloned()
+            .collect()
+    }
+
+    // Shows the current node, ending the conversation when nothing can be chosen
+    fn show_node(&mut self) -> Vec<String> {
+        let (idx, node) = match self.conversation_node() {
+            Option::Some(current) => current,
+            Option::None => {
+                self.conversation = Option::None;
+                return Vec::new();
+            }
+        };
+        let mut lines = Vec::new();
+        if !node.text.is_empty() {
+            lines.push(message::text("npc.says", &Args::new().set_name(&self.things[idx].name()).set("text", &node.text)));
+        }
+        let choices = self.available_choices(&node);
+        if choices.is_empty() {
+            self.conversation = Option::None;
+        }
+        choices.iter().enumerate().for_each(|(i, c)| lines.push(format!("{}. {}", i + 1, c.text)));
+        if !choices.is_empty() {
+            lines.push(format!("0. {}", message::text("talk.leave", &Args::new())));
+        }
+        lines
+    }
+
+    fn talk(&mut self, command: &Command) -> String {
+        let who = match command.objects.first() {
+            Option::Some(who) => who,
+            Option::None => return missing_object_message(&Keyword::Talk)
+        };
+        let idx = match self.find(who) {
+            Option::Some(idx) => idx,
+            Option::None => return self.not_found_message(who)
+        };
+        let start = match self.things[idx].dialogue() {
+            Option::Some(dialogue) => dialogue.start().id.clone(),
+            Option::None => return self.things[idx].resolve_command(command)
+        };
+        self.conversation = Option::Some((self.things[idx].id(), start));
+        self.show_node().join("\n")
+    }
+
+    pub fn in_conversation(&self) -> bool {
+        self.conversation.is_some()
+    }
+
+    // Picks one of the numbered options of the current conversation, or 0 to leave it
+    pub fn choose(&mut self, line: &str) -> String {
+        let (idx, node) = match self.conversation_node() {
+            Option::Some(current) => current,
+            Option::None => {
+                self.conversation = Option::None;
+                return message::text("talk.over", &Args::new());
+            }
+        };
+        let name = self.things[idx].name();
+        if !self.in_scope(self.things[idx].as_ref()) {
+            self.conversation = Option::None;
+            return message::named("talk.gone", &name);
+        }
+        let choices = self.available_choices(&node);
+        let choice = match line.trim().parse::<usize>() {
+            Ok(0) => {
+                self.conversation = Option::None;
+                return message::named("talk.stop", &name);
+            },
+            Ok(n) if n <= choices.len() => choices[n - 1].clone(),
+            _ => return message::text("talk.choose", &Args::new().set_count(choices.len() as i64))
+        };
+
+        choice.set.iter().for_each(|flag| self.set_flag(flag));
+        choice.clear.iter().for_each(|flag| self.vars.remove(flag));
+        for item in choice.give.iter() {
+            if let Option::Some(thing) = self.things.iter_mut().find(|t| t.id() == *item) {
+                thing.move_to(Location::Inventory);
+            }
+        }
+
+        let mut lines = Vec::new();
+        if !choice.reply.is_empty() {
+            lines.push(message::text("npc.says", &Args::new().set_name(&name).set("text", &choice.reply)));
+        }
+        match &choice.goto {
+            Option::Some(next) => {
+                self.conversation = Option::Some((self.things[idx].id(), next.clone()));
+                lines.append(&mut self.show_node());
+            },
+            Option::None => self.conversation = Option::None
+        }
+        if !self.in_conversation() {
+            lines.push(message::named("talk.stop", &name));
+        }
+        lines.join("\n")
+    }
+
+    fn ask_about(&mut self, command: &Command) -> String {
+        let (who, topic) = match (command.objects.first(), command.objects.get(1)) {
+            (Option::Some(who), Option::Some(topic)) => (who, topic),
+            _ => return self.default_message(Keyword::Ask)
+        };
+        match self.find(who) {
+            Option::Some(idx) => self.things[idx].resolve_command(&Command {
+                status: command.status,
+                keyword: command.keyword.clone(),
+                objects: vec!(topic.clone()),
+                spans: Vec::new(),
+                literal: command.literal.clone(),
+                quantity: command.quantity,
+                error: Option::None
+            }),
+            Option::None => self.not_found_message(who)
+        }
+    }
+
+    fn give_to(&mut self, command: &Command) -> String {
+        let (what, who) = match (command.objects.first(), command.objects.get(1)) {
+            (Option::Some(what), Option::Some(who)) => (what, who),
+            _ => return self.default_message(Keyword::Give)
+        };
+        if command.quantity == Option::Some(0) {
+            return message::text("quantity.zero", &Args::new());
+        }
+        let item = match self.find(what) {
+            Option::Some(idx) if self.things[idx].location() == Location::Inventory => idx,
+            Option::Some(idx) => return message::named("carry.missing", &self.things[idx].name()),
+            Option::None => return self.not_found_message(what)
+        };
+        let npc = match self.find(who) {
+            Option::Some(idx) => idx,
+            Option::None => return self.not_found_message(who)
+        };
+        let item_id = self.things[item].id();
+        let carried = self.things[item].stack().map(|s| s.count(&Location::Inventory)).unwrap_or(1);
+        if command.quantity.unwrap_or(0) > carried {
+            return self.too_few(item, Location::Inventory, "stack.too_few_carried");
+        }
+        let text = self.things[npc].resolve_command(&Command {
+            status: command.status,
+            keyword: command.keyword.clone(),
+            objects: vec!(item_id.clone()),
+            spans: Vec::new(),
+            literal: command.literal.clone(),
+            quantity: command.quantity,
+            error: Option::None
+        });
+        if self.things[npc].accepts(&item_id) {
+            let holder = Location::HeldBy(self.things[npc].id());
+            match self.things[item].stack_mut() {
+                Option::Some(stack) => {
+                    stack.move_count(&Location::Inventory, holder, command.quantity);
+                },
+                Option::None => self.things[item].move_to(holder)
+            }
+        }
+        text
+    }
+
+    // Says there are fewer units of a stack than asked for
+    fn too_few(&self, idx: usize, location: Location, id: &str) -> String {
+        let count = self.things[idx].stack().map(|s| s.count(&location)).unwrap_or(0);
+        let name = self.things[idx].stack().map(|s| s.counted(&self.things[idx].name(), count)).unwrap_or_default();
+        message::text(id, &Args::new().set("counted", &name).set_count(count as i64))
+    }
+
+    // Takes some or all of a stack lying in the room
+    fn take_stack(&mut self, idx: usize, command: &Command) -> String {
+        if command.quantity == Option::Some(0) {
+            return message::text("quantity.zero", &Args::new());
+        }
+        let here = Location::Room(self.location.clone());
+        let name = self.things[idx].name();
+        let moved = match self.things[idx].stack_mut() {
+            Option::Some(stack) if stack.count(&here) == 0 => return message::text("take.already", &Args::new()),
+            Option::Some(stack) => stack.move_count(&here, Location::Inventory, command.quantity),
+            Option::None => 0
+        };
+        match self.things[idx].stack() {
+            Option::Some(stack) if moved > 0 => message::text("take.count", &Args::new().set("counted", &stack.counted(&name, moved))),
+            _ => self.too_few(idx, here, "stack.too_few_here")
+        }
+    }
+
+    // Leaves something carried, or some of a stack, in the room
+    fn drop(&mut self, idx: usize, command: &Command) -> String {
+        let here = Location::Room(self.location.clone());
+        let name = self.things[idx].name();
+        if self.things[idx].location() != Location::Inventory {
+            return message::named("carry.missing", &name);
+        }
+        if command.quantity == Option::Some(0) {
+            return message::text("quantity.zero", &Args::new());
+        }
+        let moved = match self.things[idx].stack_mut() {
+            Option::Some(stack) => stack.move_count(&Location::Inventory, here, command.quantity),
+            Option::None => {
+                self.things[idx].move_to(here);
+                return message::named("drop.done", &name);
+            }
+        };
+        match self.things[idx].stack() {
+            Option::Some(stack) if moved > 0 => message::text("drop.count", &Args::new().set("counted", &stack.counted(&name, moved))),
+            _ => self.too_few(idx, Location::Inventory, "stack.too_few_carried")
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        !self.player.is_alive()
+    }
+
+    // Whatever the dead creature carried falls to the floor
+    fn drop_loot(&mut self, idx: usize) {
+        let holder = Location::HeldBy(self.things[idx].id());
+        let floor = self.things[idx].location();
+        self.things.iter_mut().for_each(|t| t.move_from(&holder, floor.clone()));
+    }
+
+    fn attack(&mut self, command: &Command) -> String {
+        let target = match command.objects.first() {
+            Option::Some(target) => target,
+            Option::None => return missing_object_message(&Keyword::Attack)
+        };
+        let idx = match self.find(target) {
+            Option::Some(idx) => idx,
+            Option::None => return self.not_found_message(target)
+        };
+        let mut attacker = self.player.clone();
+        if let Option::Some(weapon) = command.objects.get(1) {
+            match self.find(weapon) {
+                Option::Some(w) if self.things[w].location() != Location::Inventory => {
+                    return message::named("carry.missing", &self.things[w].name())
+                },
+                Option::Some(w) => match self.things[w].damage() {
+                    Option::Some(damage) => attacker.damage = damage,
+                    Option::None => return message::named("attack.not_weapon", &self.things[w].name())
+                },
+                Option::None => return self.not_found_message(weapon)
+            }
+        }
+
+        let name = self.things[idx].name();
+        self.things[idx].provoke();
+        let result = match self.things[idx].stats_mut() {
+            Option::Some(stats) if !stats.is_alive() => return message::named("attack.already_dead", &name),
+            Option::Some(stats) => combat::strike(&mut self.rng, &attacker, stats),
+            Option::None => return self.default_message(Keyword::Attack)
+        };
+        let killed = !self.things[idx].stats().map(|s| s.is_alive()).unwrap_or(true);
+        match result {
+            Option::Some(_) if killed => {
+                self.drop_loot(idx);
+                message::named("attack.kill", &name)
+            },
+            Option::Some(damage) => message::text("attack.hit", &Args::new().set_name(&name).set_count(damage as i64)),
+            Option::None => message::named("attack.miss", &name)
+        }
+    }
+
+    // Hostile creatures in the room fight the player
+    fn creatures_act(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        for idx in 0..self.things.len() {
+            if !self.player.is_alive() {
+                break;
+            }
+            if !self.things[idx].is_hostile() || !self.things[idx].is_at(&self.location) {
+                continue;
+            }
+            let stats = match self.things[idx].stats() {
+                Option::Some(stats) => stats.clone(),
+                Option::None => continue
+            };
+            let name = self.things[idx].name();
+            messages.push(match combat::strike(&mut self.rng, &stats, &mut self.player) {
+                Option::Some(damage) if !self.player.is_alive() => message::text("attacked.fatal", &Args::new().set_name(&name).set_count(damage as i64)),
+                Option::Some(damage) => message::text("attacked.hit", &Args::new().set_name(&name).set_count(damage as i64).set("hp", &self.player.hp.to_string())),
+                Option::None => message::named("attacked.miss", &name)
+            });
+        }
+        messages
+    }
+
+    fn resolve_object_command(&mut self, keyword: &Keyword, command: &Command) -> String {
+        let target = match command.objects.first() {
+            Option::Some(target) => target,
+            Option::None => return missing_object_message(keyword)
+        };
+        let idx = match self.find(target) {
+            Option::Some(idx) => idx,
+            Option::None => return self.not_found_message(target)
+        };
+
+        // The target receives the rest of the objects already resolved to ids. A key must be
+        // carried to open with it
+        let mut args = Vec::new();
+        for obj in command.objects.iter().skip(1) {
+            match self.find(obj) {
+                Option::Some(i) if *keyword == Keyword::OpenWith && self.things[i].location() != Location::Inventory => {
+                    return message::named("carry.missing", &self.things[i].name())
+                },
+                Option::Some(i) => args.push(self.things[i].id()),
+                Option::None => return self.not_found_message(obj)
+            }
+        }
+        let delegated = Command {
+            status: command.status,
+            keyword: command.keyword.clone(),
+            objects: args,
+            spans: Vec::new(),
+            literal: command.literal.clone(),
+            quantity: command.quantity,
+            error: Option::None
+        };
+
+        // A trigger with nothing to say leaves the verb to do what it would
+        let (verb, id) = (trigger::verb(keyword), self.things[idx].id());
+        if let Option::Some(text) = self.fire_triggers(&When::Verb(verb.to_string(), id.clone())).filter(|text| !text.is_empty()) {
+            return text;
+        }
+        match self.behaviours.iter().find(|b| b.thing == id && b.verb == verb).map(|b| b.script.clone()) {
+            Option::Some(script) => self.run_script(&script, idx, &delegated),
+            Option::None => self.object_behaviour(idx, keyword, &delegated)
+        }
+    }
+
+    // What the thing does on its own when the verb is applied to it
+    fn object_behaviour(&mut self, idx: usize, keyword: &Keyword, command: &Command) -> String {
+        match keyword {
+            Keyword::GoThrough => self.go_through(idx, command),
+            Keyword::Take if self.things[idx].stack().is_some() => self.take_stack(idx, command),
+            Keyword::Drop => self.drop(idx, command),
+            _ => self.things[idx].resolve_command(command)
+        }
+    }
+
+    fn run_script(&mut self, script: &Script, idx: usize, command: &Command) -> String {
+        let mut host = ScriptHost { world: self, idx, command: command.clone(), output: Vec::new() };
+        if let Err(e) = script.run(&mut host) {
+            host.output.push(format!("[Script error: {}]", e));
+        }
+        host.output.join("\n")
+    }
+
+    fn register_timers(&mut self) {
+        let clock = &mut self.clock;
+        for thing in self.things.iter_mut() {
+            let id = thing.id();
+            thing.timers().iter().for_each(|timer| clock.schedule(&id, timer, ""));
+        }
+    }
+
+    fn fire(&mut self, event: &Event) -> Option<String> {
+        let idx = match self.things.iter().position(|t| t.id() == event.target) {
+            Option::Some(idx) => idx,
+            Option::None => return if event.message.is_empty() { Option::None } else { Option::Some(event.message.clone()) }
+        };
+        let visible = self.is_visible(self.things[idx].as_ref());
+        let text = self.things[idx].on_event(&event.action);
+        if !visible {
+            return Option::None;
+        }
+        match text {
+            Option::Some(text) if !event.message.is_empty() => Option::Some(format!("{}\n{}", event.message, text)),
+            Option::Some(text) => Option::Some(text),
+            Option::None if !event.message.is_empty() => Option::Some(event.message.clone()),
+            Option::None => Option::None
+        }
+    }
+
+    // Advances the world one turn, returning what the player notices
+    pub fn advance(&mut self) -> Vec<String> {
+        let mut messages = Vec::new();
+        let lit = self.is_lit();
+        for idx in 0..self.things.len() {
+            // Whoever the player is talking to waits for the conversation to end
+            if self.conversation.as_ref().map(|(npc, _)| *npc == self.things[idx].id()).unwrap_or(false) {
+                continue;
+            }
+            let before = self.is_visible(self.things[idx].as_ref());
+            let text = self.things[idx].tick();
+            let after = self.is_visible(self.things[idx].as_ref());
+            if let Option::Some(text) = text {
+                if before || after {
+                    messages.push(text);
+                }
+            }
+            if lit && before != after {
+                let name = self.things[idx].name();
+                messages.push(message::named(if after { "npc.arrives" } else { "npc.leaves" }, &name));
+            }
+        }
+        for event in self.clock.advance().iter() {
+            if let Option::Some(text) = self.fire(event) {
+                messages.push(text);
+            }
+        }
+        if let Option::Some(text) = self.fire_triggers(&When::Turn(self.clock.turn())) {
+            messages.push(text);
+        }
+        messages.append(&mut self.creatures_act());
+        self.register_timers();
+        messages.append(&mut self.update_score());
+        messages
+    }
+
+}
+
+impl HandleCommand for World {
+
+    fn resolve_command(&mut self, command: &Command) -> String {
+        match &command.keyword {
+            Option::Some(Keyword::View) if command.objects.is_empty() => self.look(),
+            Option::Some(Keyword::Inventory) => self.inventory(),
+            Option::Some(Keyword::Score) => self.score(),
+            Option::Some(Keyword::Debug) => self.debug(command),
+            Option::Some(Keyword::Talk) => self.talk(command),
+            Option::Some(Keyword::Say) => self.say(command),
+            Option::Some(Keyword::AskAbout) => self.ask_about(command),
+            Option::Some(Keyword::GiveTo) => self.give_to(command),
+            Option::Some(Keyword::Attack) | Option::Some(Keyword::AttackWith) => self.attack(command),
+            Option::Some(Keyword::Ask) | Option::Some(Keyword::Give) | Option::None => {
+                DefaultCommandHandler::new().resolve_command(command)
+            },
+            Option::Some(keyword) => {
+                let text = self.resolve_object_command(&keyword.clone(), command);
+                self.register_timers();
+                text
+            }
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::parser::StateMachine;
+
+    #[test]
+    fn random_range_is_clamped() {
+        assert_eq!(random_range(1, 6), (1, 6));
+        assert_eq!(random_range(-5, -1), (0, 0));
+        assert_eq!(random_range(6, 1), (6, 6));
+        assert_eq!(random_range(0, i64::MAX), (0, u32::MAX));
+        assert_eq!(random_range(i64::MIN, 1 << 40), (0, u32::MAX));
+    }
+
+    #[test]
+    fn a_verb_trigger_without_a_message_leaves_the_verb_alone() {
+        let mut world = World::load(r#"
+            title = "Garden"
+
+            [player]
+            location = "garden"
+
+            [[room]]
+            id = "garden"
+            name = "Garden"
+            description = "A garden."
+
+            [[thing]]
+            kind = "item"
+            id = "stone"
+            name = "stone"
+            description = "A stone."
+            location = "garden"
+
+            [[trigger]]
+            on = "take:stone"
+            do = ["set:moved"]
+        "#).unwrap();
+        let take = StateMachine::build().parse_line("take stone");
+        assert_eq!(world.resolve_command(&take), "You take the stone");
+        assert!(world.vars.is_set("moved"));
+    }
+
+    #[test]
+    fn a_key_opens_a_door_only_when_carried() {
+        let mut world = World::load(r#"
+            title = "Cell"
+
+            [player]
+            location = "cell"
+
+            [[room]]
+            id = "cell"
+            name = "Cell"
+            description = "A cell."
+
+            [[room]]
+            id = "corridor"
+            name = "Corridor"
+            description = "A corridor."
+
+            [[thing]]
+            kind = "locked_door"
+            id = "cell_door"
+            name = "cell door"
+            rooms = ["cell", "corridor"]
+            key = "key"
+
+            [[thing]]
+            kind = "item"
+            id = "key"
+            name = "key"
+            description = "A key."
+            location = "cell"
+        "#).unwrap();
+        let parser = StateMachine::build();
+        let open = parser.parse_line("open cell door with key");
+        assert_eq!(world.resolve_command(&open), "You don't have the key");
+        world.resolve_command(&parser.parse_line("take key"));
+        assert_eq!(world.resolve_command(&open), "You open the cell door");
+    }
+
+    #[test]
+    fn loading_keeps_the_messages_in_the_world() {
+        message::set_overrides(&[]);
+        let world = World::load(include_str!("../../resources/worlds/fortress.toml")).unwrap();
+        assert_eq!(message::named("door.locked", "door"), "The door is locked");
+        message::set_overrides(world.messages());
+        assert_eq!(message::named("door.locked", "door"), "The door won't budge. It must be locked.");
+        message::set_overrides(&[]);
+    }
+
+}
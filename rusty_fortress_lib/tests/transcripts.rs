@@ -0,0 +1,26 @@
+use std::fs;
+use std::path::Path;
+use rusty_fortress_lib::app::app::Options;
+use rusty_fortress_lib::app::script;
+
+// Plays every golden transcript shipped with the game and fails on any difference
+#[test]
+fn transcripts_match() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("resources/transcripts");
+    let mut files: Vec<_> = fs::read_dir(&dir).expect("The transcripts directory must exist")
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|e| e == "txt").unwrap_or(false))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "No transcripts found in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for file in files.iter() {
+        match script::test_file(&file.to_string_lossy(), Options::new()) {
+            Ok(diffs) if diffs.is_empty() => {},
+            Ok(diffs) => failures.push(format!("{}:\n{}", file.display(), diffs.join("\n"))),
+            Err(e) => failures.push(format!("{}: {}", file.display(), e))
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n\n"));
+}